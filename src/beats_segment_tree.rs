@@ -0,0 +1,380 @@
+use crate::segment_tree::bounds;
+use num::PrimInt;
+use std::cmp::Ordering;
+use std::ops::RangeBounds;
+
+#[derive(Clone, Copy, Debug)]
+struct Node<T> {
+    sum: T,
+    max: T,
+    /// The largest element smaller than `max`, if there's one.
+    max2: Option<T>,
+    max_cnt: usize,
+    min: T,
+    /// The smallest element greater than `min`, if there's one.
+    min2: Option<T>,
+    min_cnt: usize,
+    /// The pending addition to the children.
+    add: T,
+}
+
+impl<T: PrimInt> Node<T> {
+    fn leaf(x: T) -> Self {
+        Self {
+            sum: x,
+            max: x,
+            max2: None,
+            max_cnt: 1,
+            min: x,
+            min2: None,
+            min_cnt: 1,
+            add: T::zero(),
+        }
+    }
+
+    fn cnt(cnt: usize) -> T {
+        T::from(cnt).unwrap()
+    }
+
+    /// Adds `x` to every element of the node, which covers `len` elements.
+    fn apply_add(&mut self, len: usize, x: T) {
+        self.sum = self.sum + x * Self::cnt(len);
+        self.max = self.max + x;
+        self.max2 = self.max2.map(|m| m + x);
+        self.min = self.min + x;
+        self.min2 = self.min2.map(|m| m + x);
+        self.add = self.add + x;
+    }
+
+    /// Lowers the maximum to `x`, which has to be greater than `max2`.
+    fn apply_chmin(&mut self, x: T) {
+        self.sum = self.sum - (self.max - x) * Self::cnt(self.max_cnt);
+        if self.max == self.min {
+            self.min = x;
+        } else if Some(self.max) == self.min2 {
+            self.min2 = Some(x);
+        }
+        self.max = x;
+    }
+
+    /// Raises the minimum to `x`, which has to be smaller than `min2`.
+    fn apply_chmax(&mut self, x: T) {
+        self.sum = self.sum + (x - self.min) * Self::cnt(self.min_cnt);
+        if self.min == self.max {
+            self.max = x;
+        } else if Some(self.min) == self.max2 {
+            self.max2 = Some(x);
+        }
+        self.min = x;
+    }
+
+    /// Applies the pending operations of `parent` to the node, which covers `len` elements.
+    fn apply_parent(&mut self, len: usize, parent: &Self) {
+        if parent.add != T::zero() {
+            self.apply_add(len, parent.add);
+        }
+        if parent.max < self.max {
+            self.apply_chmin(parent.max);
+        }
+        if self.min < parent.min {
+            self.apply_chmax(parent.min);
+        }
+    }
+
+    fn merge(left: &Self, right: &Self) -> Self {
+        let (max, max2, max_cnt) = match left.max.cmp(&right.max) {
+            Ordering::Equal => (
+                left.max,
+                left.max2.max(right.max2),
+                left.max_cnt + right.max_cnt,
+            ),
+            Ordering::Greater => (left.max, left.max2.max(Some(right.max)), left.max_cnt),
+            Ordering::Less => (right.max, right.max2.max(Some(left.max)), right.max_cnt),
+        };
+        // `None` stands for infinity here, so it can't be compared with `Option::min`.
+        let min_opt = |a: Option<T>, b: Option<T>| match (a, b) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, None) => a,
+            (None, b) => b,
+        };
+        let (min, min2, min_cnt) = match left.min.cmp(&right.min) {
+            Ordering::Equal => (
+                left.min,
+                min_opt(left.min2, right.min2),
+                left.min_cnt + right.min_cnt,
+            ),
+            Ordering::Less => (left.min, min_opt(left.min2, Some(right.min)), left.min_cnt),
+            Ordering::Greater => (
+                right.min,
+                min_opt(right.min2, Some(left.min)),
+                right.min_cnt,
+            ),
+        };
+
+        Self {
+            sum: left.sum + right.sum,
+            max,
+            max2,
+            max_cnt,
+            min,
+            min2,
+            min_cnt,
+            add: T::zero(),
+        }
+    }
+}
+
+/// A segment tree beats (Ji Driver's segment tree), which supports range `chmin`, `chmax` and
+/// addition together with range sum, maximum and minimum.
+#[derive(Clone, Debug)]
+pub struct BeatsSegmentTree<T: PrimInt> {
+    len: usize,
+    nodes: Vec<Node<T>>,
+}
+
+impl<T: PrimInt> BeatsSegmentTree<T> {
+    /// Creates a tree of `length` zeros.
+    pub fn new(length: usize) -> Self {
+        let mut slf = Self {
+            len: length,
+            nodes: vec![Node::leaf(T::zero()); 4 * length],
+        };
+        slf.build((0..length).map(|_| T::zero()));
+        slf
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Pushes the pending operations of the node down to its children.
+    fn push(&mut self, node: usize, lo: usize, hi: usize) {
+        let mid = (lo + hi) / 2;
+        let parent = self.nodes[node];
+        self.nodes[2 * node + 1].apply_parent(mid - lo, &parent);
+        self.nodes[2 * node + 2].apply_parent(hi - mid, &parent);
+        self.nodes[node].add = T::zero();
+    }
+
+    fn pull(&mut self, node: usize) {
+        self.nodes[node] = Node::merge(&self.nodes[2 * node + 1], &self.nodes[2 * node + 2]);
+    }
+
+    /// Sets the first elements to the elements of `iter`.
+    ///
+    /// O(n)
+    pub fn build<Iter: IntoIterator<Item = T>>(&mut self, iter: Iter) {
+        fn build_rec<T: PrimInt, Iter: Iterator<Item = T>>(
+            slf: &mut BeatsSegmentTree<T>,
+            node: usize,
+            lo: usize,
+            hi: usize,
+            iter: &mut Iter,
+        ) {
+            if hi - lo == 1 {
+                if let Some(x) = iter.next() {
+                    slf.nodes[node] = Node::leaf(x);
+                }
+            } else {
+                let mid = (lo + hi) / 2;
+                slf.push(node, lo, hi);
+                build_rec(slf, 2 * node + 1, lo, mid, iter);
+                build_rec(slf, 2 * node + 2, mid, hi, iter);
+                slf.pull(node);
+            }
+        }
+
+        if !self.is_empty() {
+            build_rec(self, 0, 0, self.len(), &mut iter.into_iter());
+        }
+    }
+
+    /// Replaces every element `a` in `range` with `min(a, x)`.
+    ///
+    /// Amortized O(log² n)
+    pub fn chmin<R: RangeBounds<usize>>(&mut self, range: R, x: T) {
+        fn chmin_rec<T: PrimInt>(
+            slf: &mut BeatsSegmentTree<T>,
+            node: usize,
+            lo: usize,
+            hi: usize,
+            l: usize,
+            r: usize,
+            x: T,
+        ) {
+            if r <= lo || hi <= l || slf.nodes[node].max <= x {
+                return;
+            }
+            if l <= lo && hi <= r && !matches!(slf.nodes[node].max2, Some(m) if x <= m) {
+                slf.nodes[node].apply_chmin(x);
+                return;
+            }
+
+            let mid = (lo + hi) / 2;
+            slf.push(node, lo, hi);
+            chmin_rec(slf, 2 * node + 1, lo, mid, l, r, x);
+            chmin_rec(slf, 2 * node + 2, mid, hi, l, r, x);
+            slf.pull(node);
+        }
+
+        let (l, r) = bounds(range, self.len());
+        if l < r {
+            chmin_rec(self, 0, 0, self.len(), l, r, x);
+        }
+    }
+
+    /// Replaces every element `a` in `range` with `max(a, x)`.
+    ///
+    /// Amortized O(log² n)
+    pub fn chmax<R: RangeBounds<usize>>(&mut self, range: R, x: T) {
+        fn chmax_rec<T: PrimInt>(
+            slf: &mut BeatsSegmentTree<T>,
+            node: usize,
+            lo: usize,
+            hi: usize,
+            l: usize,
+            r: usize,
+            x: T,
+        ) {
+            if r <= lo || hi <= l || x <= slf.nodes[node].min {
+                return;
+            }
+            if l <= lo && hi <= r && !matches!(slf.nodes[node].min2, Some(m) if m <= x) {
+                slf.nodes[node].apply_chmax(x);
+                return;
+            }
+
+            let mid = (lo + hi) / 2;
+            slf.push(node, lo, hi);
+            chmax_rec(slf, 2 * node + 1, lo, mid, l, r, x);
+            chmax_rec(slf, 2 * node + 2, mid, hi, l, r, x);
+            slf.pull(node);
+        }
+
+        let (l, r) = bounds(range, self.len());
+        if l < r {
+            chmax_rec(self, 0, 0, self.len(), l, r, x);
+        }
+    }
+
+    /// Adds `x` to every element in `range`.
+    ///
+    /// O(log n)
+    pub fn add<R: RangeBounds<usize>>(&mut self, range: R, x: T) {
+        fn add_rec<T: PrimInt>(
+            slf: &mut BeatsSegmentTree<T>,
+            node: usize,
+            lo: usize,
+            hi: usize,
+            l: usize,
+            r: usize,
+            x: T,
+        ) {
+            if r <= lo || hi <= l {
+                return;
+            }
+            if l <= lo && hi <= r {
+                slf.nodes[node].apply_add(hi - lo, x);
+                return;
+            }
+
+            let mid = (lo + hi) / 2;
+            slf.push(node, lo, hi);
+            add_rec(slf, 2 * node + 1, lo, mid, l, r, x);
+            add_rec(slf, 2 * node + 2, mid, hi, l, r, x);
+            slf.pull(node);
+        }
+
+        let (l, r) = bounds(range, self.len());
+        if l < r {
+            add_rec(self, 0, 0, self.len(), l, r, x);
+        }
+    }
+
+    /// Returns the sum, maximum and minimum of `l..r`, which isn't empty.
+    fn fold(&self, l: usize, r: usize) -> (T, T, T) {
+        // `node` is the node with the pending operations of its ancestors applied to it.
+        fn fold_rec<T: PrimInt>(
+            slf: &BeatsSegmentTree<T>,
+            node: usize,
+            value: &Node<T>,
+            lo: usize,
+            hi: usize,
+            l: usize,
+            r: usize,
+        ) -> (T, T, T) {
+            if l <= lo && hi <= r {
+                return (value.sum, value.max, value.min);
+            }
+
+            let mid = (lo + hi) / 2;
+            let mut left = slf.nodes[2 * node + 1];
+            left.apply_parent(mid - lo, value);
+            let mut right = slf.nodes[2 * node + 2];
+            right.apply_parent(hi - mid, value);
+
+            if r <= mid {
+                fold_rec(slf, 2 * node + 1, &left, lo, mid, l, r)
+            } else if mid <= l {
+                fold_rec(slf, 2 * node + 2, &right, mid, hi, l, r)
+            } else {
+                let (ls, lmax, lmin) = fold_rec(slf, 2 * node + 1, &left, lo, mid, l, r);
+                let (rs, rmax, rmin) = fold_rec(slf, 2 * node + 2, &right, mid, hi, l, r);
+                (ls + rs, lmax.max(rmax), lmin.min(rmin))
+            }
+        }
+
+        fold_rec(self, 0, &self.nodes[0], 0, self.len(), l, r)
+    }
+
+    /// O(log n)
+    pub fn sum<R: RangeBounds<usize>>(&self, range: R) -> T {
+        let (l, r) = bounds(range, self.len());
+        if l < r {
+            self.fold(l, r).0
+        } else {
+            T::zero()
+        }
+    }
+
+    /// Returns the maximum in `range`, or `T::min_value()` if it's empty.
+    ///
+    /// O(log n)
+    pub fn max<R: RangeBounds<usize>>(&self, range: R) -> T {
+        let (l, r) = bounds(range, self.len());
+        if l < r {
+            self.fold(l, r).1
+        } else {
+            T::min_value()
+        }
+    }
+
+    /// Returns the minimum in `range`, or `T::max_value()` if it's empty.
+    ///
+    /// O(log n)
+    pub fn min<R: RangeBounds<usize>>(&self, range: R) -> T {
+        let (l, r) = bounds(range, self.len());
+        if l < r {
+            self.fold(l, r).2
+        } else {
+            T::max_value()
+        }
+    }
+
+    /// O(log n)
+    pub fn get(&self, i: usize) -> T {
+        assert!(
+            i < self.len(),
+            "index {} is out of bounds for length {}",
+            i,
+            self.len()
+        );
+        self.fold(i, i + 1).0
+    }
+}
@@ -0,0 +1,120 @@
+use crate::{
+    groups::{AbelianGroup, CommutativeMonoid},
+    FenwickTree,
+};
+use std::fmt;
+use std::ops::{Bound, RangeBounds};
+
+/// A Fenwick tree indexed by a fixed set of sparse keys, which are mapped to their ranks.
+#[derive(Clone)]
+pub struct CompressedFenwickTree<K: Ord, G: CommutativeMonoid> {
+    /// The sorted distinct keys.
+    keys: Vec<K>,
+    tree: FenwickTree<G>,
+}
+
+impl<K: Ord, G: CommutativeMonoid> CompressedFenwickTree<K, G> {
+    /// O(n log n)
+    pub fn new<Iter: IntoIterator<Item = K>>(group: G, keys: Iter) -> Self {
+        let mut keys: Vec<K> = keys.into_iter().collect();
+        keys.sort_unstable();
+        keys.dedup();
+
+        let mut tree = FenwickTree::with_capacity(group, keys.len());
+        tree.resize_id(keys.len());
+        Self { keys, tree }
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// The sorted keys.
+    #[inline]
+    pub fn keys(&self) -> &[K] {
+        &self.keys
+    }
+
+    /// The rank of `key`, or `None` if it isn't one of the keys.
+    ///
+    /// O(log n)
+    #[inline]
+    pub fn rank(&self, key: &K) -> Option<usize> {
+        self.keys.binary_search(key).ok()
+    }
+
+    /// Panics if `key` isn't one of the keys.
+    ///
+    /// O(log n)
+    pub fn update_add(&mut self, key: &K, dx: G::Elem) {
+        let i = self
+            .rank(key)
+            .expect("`key` isn't one of the keys of the tree");
+        self.tree.update_add(i, dx);
+    }
+
+    /// Sums the elements of the keys smaller than `key`, which doesn't have to be one of the keys.
+    ///
+    /// O(log n)
+    pub fn prefix_sum(&self, key: &K) -> G::Elem {
+        self.tree.prefix_sum(self.keys.partition_point(|k| k < key))
+    }
+}
+
+impl<K: Ord, G: AbelianGroup> CompressedFenwickTree<K, G> {
+    /// Sums the elements of the keys in `range`.
+    ///
+    /// O(log n)
+    pub fn range_sum<R: RangeBounds<K>>(&self, range: R) -> G::Elem {
+        let l = match range.start_bound() {
+            Bound::Included(key) => self.keys.partition_point(|k| k < key),
+            Bound::Excluded(key) => self.keys.partition_point(|k| k <= key),
+            Bound::Unbounded => 0,
+        };
+        let r = match range.end_bound() {
+            Bound::Included(key) => self.keys.partition_point(|k| k <= key),
+            Bound::Excluded(key) => self.keys.partition_point(|k| k < key),
+            Bound::Unbounded => self.len(),
+        };
+        self.tree.range_sum(l..r.max(l))
+    }
+
+    /// Panics if `key` isn't one of the keys.
+    ///
+    /// O(log n)
+    pub fn get(&self, key: &K) -> G::Elem {
+        let i = self
+            .rank(key)
+            .expect("`key` isn't one of the keys of the tree");
+        self.tree.get(i)
+    }
+
+    /// `update_add` should be perferred over this, because it's faster (it doesn't call `get`).
+    ///
+    /// O(log n)
+    pub fn update_set(&mut self, key: &K, x: G::Elem) {
+        let i = self
+            .rank(key)
+            .expect("`key` isn't one of the keys of the tree");
+        self.tree.update_set(i, x);
+    }
+}
+
+impl<K: Ord + fmt::Debug, G: CommutativeMonoid + fmt::Debug> fmt::Debug
+    for CompressedFenwickTree<K, G>
+where
+    G::Elem: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CompressedFenwickTree")
+            .field("keys", &self.keys)
+            .field("tree", &self.tree)
+            .finish()
+    }
+}
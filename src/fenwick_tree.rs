@@ -1,7 +1,11 @@
-use crate::groups::{AbelianGroup, CommutativeMonoid};
+use crate::{
+    groups::{AbelianGroup, CommutativeMonoid, NumAdditiveGroups},
+    segment_tree::bounds,
+};
+use std::ops::RangeBounds;
 
 #[inline]
-fn lsb<I: num::PrimInt>(n: I) -> I {
+pub(crate) fn lsb<I: num::PrimInt>(n: I) -> I {
     n & (!n + I::one())
 }
 
@@ -95,6 +99,42 @@ impl<G: CommutativeMonoid> FenwickTree<G> {
         }
     }
 
+    /// Constructs a new `FenwickTree<G>` of the elements of `data`, in place.
+    ///
+    /// O(n)
+    pub fn from_vec(group: G, data: Vec<G::Elem>) -> Self {
+        let mut slf = Self { group, data };
+        slf.build_from(0);
+        slf
+    }
+
+    /// Turns the elements from `start` onwards into nodes of the tree, given that the nodes before
+    /// `start` are already built.
+    ///
+    /// O(n - start + log n)
+    fn build_from(&mut self, start: usize) {
+        // Adds the built nodes whose parents are new, which are the nodes of `prefix_sum(start)`.
+        let mut i = start;
+        while i != 0 {
+            let parent = (i - 1) | i;
+            if parent < self.len() {
+                self.data[parent] = self
+                    .group
+                    .add(self.data[parent].clone(), self.data[i - 1].clone());
+            }
+            i -= lsb(i);
+        }
+
+        for i in start..self.len() {
+            let parent = i | (i + 1);
+            if parent < self.len() {
+                self.data[parent] = self
+                    .group
+                    .add(self.data[parent].clone(), self.data[i].clone());
+            }
+        }
+    }
+
     /// Reserves capacity for at least `additional` more elements to be inserted
     /// in the given `FenwickTree<G>`. The collection may reserve more space to avoid
     /// frequent reallocations. After calling `reserve`, capacity will be
@@ -122,6 +162,19 @@ impl<G: CommutativeMonoid> FenwickTree<G> {
     }
 }
 
+impl<T: num::Num + num::Unsigned + Clone> FenwickTree<NumAdditiveGroups<T>> {
+    /// Subtracts `dx` from the `i`th element, which has to be at least `dx` because unsigned
+    /// numbers don't form a group.
+    ///
+    /// O(log n)
+    pub fn update_sub(&mut self, mut i: usize, dx: T) {
+        while i < self.len() {
+            self.data[i] = self.data[i].clone() - dx.clone();
+            i |= lsb(!i);
+        }
+    }
+}
+
 impl<G: AbelianGroup> FenwickTree<G> {
     /// `update_add` should be perferred over this, because it's faster (it doesn't call `get`).
     ///
@@ -140,6 +193,77 @@ impl<G: AbelianGroup> FenwickTree<G> {
         }
         x
     }
+
+    /// O(log n)
+    pub fn range_sum<R: RangeBounds<usize>>(&self, range: R) -> G::Elem {
+        let (l, r) = bounds(range, self.len());
+        self.group.sub(self.prefix_sum(r), self.prefix_sum(l))
+    }
+
+    /// Turns the nodes of `data` back into elements, in place.
+    fn unbuild(&self, data: &mut [G::Elem]) {
+        for i in (0..data.len()).rev() {
+            let parent = i | (i + 1);
+            if parent < data.len() {
+                data[parent] = self.group.sub(data[parent].clone(), data[i].clone());
+            }
+        }
+    }
+
+    /// Returns the elements, in place.
+    ///
+    /// O(n)
+    pub fn into_values(mut self) -> Vec<G::Elem> {
+        let mut data = std::mem::take(&mut self.data);
+        self.unbuild(&mut data);
+        data
+    }
+
+    /// O(n)
+    pub fn to_vec(&self) -> Vec<G::Elem> {
+        let mut data = self.data.clone();
+        self.unbuild(&mut data);
+        data
+    }
+}
+
+impl<G: CommutativeMonoid> FenwickTree<G>
+where
+    G::Elem: PartialOrd,
+{
+    /// Returns the smallest `i` such that `prefix_sum(i) >= target`, or `None` if there's no such
+    /// `i`. The prefix sums have to be non-decreasing, like when all the elements are non-negative.
+    ///
+    /// O(log n)
+    pub fn lower_bound(&self, target: &G::Elem) -> Option<usize> {
+        if self.group.id() >= *target {
+            return Some(0);
+        }
+
+        // `i` is the largest index such that `ps = prefix_sum(i) < target`.
+        let mut i = 0;
+        let mut ps = self.group.id();
+        let mut step = match self.len() {
+            0 => 0,
+            len => 1 << (usize::BITS - 1 - len.leading_zeros()),
+        };
+        while step != 0 {
+            if i + step <= self.len() {
+                let next = self.group.add(ps.clone(), self.data[i + step - 1].clone());
+                if next < *target {
+                    i += step;
+                    ps = next;
+                }
+            }
+            step >>= 1;
+        }
+
+        if i < self.len() {
+            Some(i + 1)
+        } else {
+            None
+        }
+    }
 }
 
 impl<G: CommutativeMonoid> Extend<G::Elem> for FenwickTree<G> {
@@ -150,8 +274,8 @@ impl<G: CommutativeMonoid> Extend<G::Elem> for FenwickTree<G> {
             (len, None) => self.reserve(len),
         }
 
-        for x in iter {
-            self.push(x);
-        }
+        let start = self.len();
+        self.data.extend(iter);
+        self.build_from(start);
     }
 }
@@ -0,0 +1,116 @@
+use crate::{
+    fenwick_tree::lsb,
+    groups::{AbelianGroup, CommutativeMonoid},
+};
+use std::ops::Range;
+
+/// A Fenwick tree over a `width` by `height` grid.
+#[derive(Clone, Debug)]
+pub struct FenwickTree2D<G: CommutativeMonoid> {
+    group: G,
+    width: usize,
+    height: usize,
+    data: Vec<G::Elem>,
+}
+
+impl<G: CommutativeMonoid> FenwickTree2D<G> {
+    #[inline]
+    pub fn new(group: G, width: usize, height: usize) -> Self {
+        let mut data = Vec::new();
+        data.resize_with(width * height, || group.id());
+        Self {
+            group,
+            width,
+            height,
+            data,
+        }
+    }
+
+    #[inline]
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    #[inline]
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// O(log w * log h)
+    pub fn update_add(&mut self, mut x: usize, y: usize, dx: G::Elem) {
+        assert!(
+            x < self.width && y < self.height,
+            "index ({}, {}) is out of bounds for size ({}, {})",
+            x,
+            y,
+            self.width,
+            self.height
+        );
+
+        while x < self.width {
+            let row = x * self.height;
+            let mut y = y;
+            while y < self.height {
+                self.data[row + y] = self.group.add(self.data[row + y].clone(), dx.clone());
+                y |= lsb(!y);
+            }
+            x |= lsb(!x);
+        }
+    }
+
+    /// Sums the elements in `0..x` by `0..y`.
+    ///
+    /// O(log w * log h)
+    pub fn prefix_sum(&self, mut x: usize, y: usize) -> G::Elem {
+        let mut ps = self.group.id();
+        while x != 0 {
+            let row = (x - 1) * self.height;
+            let mut y = y;
+            while y != 0 {
+                ps = self.group.add(self.data[row + y - 1].clone(), ps);
+                y -= lsb(y);
+            }
+            x -= lsb(x);
+        }
+        ps
+    }
+}
+
+impl<G: AbelianGroup> FenwickTree2D<G> {
+    /// Sums the elements in the rectangle `xs` by `ys`.
+    ///
+    /// O(log w * log h)
+    pub fn sum(&self, xs: Range<usize>, ys: Range<usize>) -> G::Elem {
+        assert!(
+            xs.start <= xs.end && xs.end <= self.width,
+            "range {:?} is out of bounds for width {}",
+            xs,
+            self.width
+        );
+        assert!(
+            ys.start <= ys.end && ys.end <= self.height,
+            "range {:?} is out of bounds for height {}",
+            ys,
+            self.height
+        );
+
+        let s = self.group.add(
+            self.prefix_sum(xs.end, ys.end),
+            self.prefix_sum(xs.start, ys.start),
+        );
+        let s = self.group.sub(s, self.prefix_sum(xs.start, ys.end));
+        self.group.sub(s, self.prefix_sum(xs.end, ys.start))
+    }
+
+    /// O(log w * log h)
+    pub fn get(&self, x: usize, y: usize) -> G::Elem {
+        self.sum(x..x + 1, y..y + 1)
+    }
+
+    /// `update_add` should be perferred over this, because it's faster (it doesn't call `get`).
+    ///
+    /// O(log w * log h)
+    pub fn update_set(&mut self, x: usize, y: usize, v: G::Elem) {
+        self.update_add(x, y, self.group.sub(v, self.get(x, y)));
+    }
+}
@@ -1,3 +1,5 @@
+pub mod laws;
+
 pub trait Magma {
     type Elem: Clone;
 
@@ -32,6 +34,122 @@ impl<G: Magma + Associativity + Identity + Invertibillity> Group for G {}
 pub trait AbelianGroup: Group + Commutativity {}
 impl<AG: Group + Commutativity> AbelianGroup for AG {}
 
+/// A monoid whose elements act on the elements of the monoid `M`.
+///
+/// The action must be compatible with both monoids:
+/// - `act(add(f, g), x) == act(f, act(g, x))`, so `add(f, g)` applies `g` first.
+/// - `act(id(), x) == x`.
+/// - `act(f, M::add(x, y)) == M::add(act(f, x), act(f, y))`.
+pub trait MonoidAction<M: Monoid>: Monoid {
+    fn act(&self, action: Self::Elem, x: M::Elem) -> M::Elem;
+}
+
+/// A monoid whose elements can be multiplied by a natural number, which is `x` added to itself
+/// `n` times.
+///
+/// The default `mul_scalar` is double-and-add, in O(log n) calls to `add`.
+pub trait ScalarMultiplication: Monoid {
+    #[inline]
+    fn mul_scalar(&self, x: Self::Elem, n: usize) -> Self::Elem {
+        double_and_add(self, x, n)
+    }
+}
+
+/// `x` added to itself `n` times, in O(log n) calls to `add`.
+fn double_and_add<M: Monoid + ?Sized>(group: &M, mut x: M::Elem, mut n: usize) -> M::Elem {
+    let mut acc = group.id();
+    while n != 0 {
+        if n & 1 != 0 {
+            acc = group.add(acc, x.clone());
+        }
+        n >>= 1;
+        // Doubling past the last bit could overflow needlessly.
+        if n != 0 {
+            x = group.add(x.clone(), x);
+        }
+    }
+    acc
+}
+
+/// Two structures over the same elements, where the multiplication distributes over the addition
+/// and the additive identity (zero) is absorbing:
+/// - `mul(a, add(b, c)) == add(mul(a, b), mul(a, c))`.
+/// - `mul(add(a, b), c) == add(mul(a, c), mul(b, c))`.
+/// - `mul(zero(), a) == zero() == mul(a, zero())`.
+pub trait Semiring {
+    type Elem: Clone;
+    type Additive: CommutativeMonoid<Elem = Self::Elem>;
+    type Multiplicative: Monoid<Elem = Self::Elem>;
+
+    fn additive(&self) -> &Self::Additive;
+    fn multiplicative(&self) -> &Self::Multiplicative;
+
+    #[inline]
+    fn zero(&self) -> Self::Elem {
+        self.additive().id()
+    }
+
+    #[inline]
+    fn one(&self) -> Self::Elem {
+        self.multiplicative().id()
+    }
+
+    #[inline]
+    fn add(&self, lhs: Self::Elem, rhs: Self::Elem) -> Self::Elem {
+        self.additive().add(lhs, rhs)
+    }
+
+    #[inline]
+    fn mul(&self, lhs: Self::Elem, rhs: Self::Elem) -> Self::Elem {
+        self.multiplicative().add(lhs, rhs)
+    }
+}
+
+/// A semiring whose additive part is an abelian group.
+pub trait Ring: Semiring {
+    fn neg(&self, x: Self::Elem) -> Self::Elem;
+    fn sub(&self, lhs: Self::Elem, rhs: Self::Elem) -> Self::Elem;
+}
+impl<R: Semiring> Ring for R
+where
+    R::Additive: Invertibillity,
+{
+    #[inline]
+    fn neg(&self, x: R::Elem) -> R::Elem {
+        self.additive().inv(x)
+    }
+
+    #[inline]
+    fn sub(&self, lhs: R::Elem, rhs: R::Elem) -> R::Elem {
+        self.additive().sub(lhs, rhs)
+    }
+}
+
+/// A ring whose multiplicative part is commutative, and a group without zero.
+///
+/// It's implemented whenever the multiplicative part is `Invertibillity`, so `NumRing<ModInt<M>>`
+/// is a `Field` for every modulus, but it obeys the laws only for a prime one. For a composite
+/// modulus `recip` and `div` panic on the elements which aren't coprime to it.
+pub trait Field: Ring {
+    /// The multiplicative inverse of `x`, which mustn't be zero.
+    fn recip(&self, x: Self::Elem) -> Self::Elem;
+    fn div(&self, lhs: Self::Elem, rhs: Self::Elem) -> Self::Elem;
+}
+impl<F: Ring> Field for F
+where
+    F::Multiplicative: Invertibillity + Commutativity,
+{
+    #[inline]
+    fn recip(&self, x: F::Elem) -> F::Elem {
+        self.multiplicative().inv(x)
+    }
+
+    #[inline]
+    fn div(&self, lhs: F::Elem, rhs: F::Elem) -> F::Elem {
+        self.multiplicative().sub(lhs, rhs)
+    }
+}
+
 #[derive(Clone, Copy, Debug)]
 pub struct NumAdditiveGroups<T>(std::marker::PhantomData<T>);
 
@@ -74,3 +192,668 @@ impl<T: num::Num + num::Signed + Clone> Invertibillity for NumAdditiveGroups<T>
 }
 impl<T: num::Num + Clone> Associativity for NumAdditiveGroups<T> {}
 impl<T: num::Num + Clone> Commutativity for NumAdditiveGroups<T> {}
+impl<T: num::Num + num::NumCast + Clone> ScalarMultiplication for NumAdditiveGroups<T> {
+    #[inline]
+    fn mul_scalar(&self, x: T, n: usize) -> T {
+        match T::from(n) {
+            Some(n) => x * n,
+            None => double_and_add(self, x, n),
+        }
+    }
+}
+
+/// Declares a zero-sized structure over the element type `T`, like `NumAdditiveGroups`.
+macro_rules! structure {
+    ($(#[$attr:meta])* $name:ident) => {
+        $(#[$attr])*
+        #[derive(Clone, Copy, Debug)]
+        pub struct $name<T>(std::marker::PhantomData<T>);
+
+        impl<T> Default for $name<T> {
+            fn default() -> Self {
+                Self::new()
+            }
+        }
+
+        impl<T> $name<T> {
+            pub const fn new() -> Self {
+                Self(std::marker::PhantomData)
+            }
+        }
+    };
+}
+
+structure! {
+    /// Multiplication of numbers. It isn't a group over the floats, since zero has no inverse.
+    NumMultiplicative
+}
+
+impl<T: num::Num + Clone> Magma for NumMultiplicative<T> {
+    type Elem = T;
+
+    #[inline]
+    fn add(&self, lhs: T, rhs: T) -> T {
+        lhs * rhs
+    }
+}
+impl<T: num::Num + Clone> Identity for NumMultiplicative<T> {
+    #[inline]
+    fn id(&self) -> T {
+        T::one()
+    }
+}
+impl<T: num::Num + Clone> Associativity for NumMultiplicative<T> {}
+impl<T: num::Num + Clone> Commutativity for NumMultiplicative<T> {}
+
+structure! {
+    /// The minimum, whose identity is `T::max_value()`.
+    Min
+}
+
+impl<T: Ord + num::Bounded + Clone> Magma for Min<T> {
+    type Elem = T;
+
+    #[inline]
+    fn add(&self, lhs: T, rhs: T) -> T {
+        lhs.min(rhs)
+    }
+}
+impl<T: Ord + num::Bounded + Clone> Identity for Min<T> {
+    #[inline]
+    fn id(&self) -> T {
+        T::max_value()
+    }
+}
+impl<T: Ord + num::Bounded + Clone> Associativity for Min<T> {}
+impl<T: Ord + num::Bounded + Clone> Commutativity for Min<T> {}
+
+structure! {
+    /// The maximum, whose identity is `T::min_value()`.
+    Max
+}
+
+impl<T: Ord + num::Bounded + Clone> Magma for Max<T> {
+    type Elem = T;
+
+    #[inline]
+    fn add(&self, lhs: T, rhs: T) -> T {
+        lhs.max(rhs)
+    }
+}
+impl<T: Ord + num::Bounded + Clone> Identity for Max<T> {
+    #[inline]
+    fn id(&self) -> T {
+        T::min_value()
+    }
+}
+impl<T: Ord + num::Bounded + Clone> Associativity for Max<T> {}
+impl<T: Ord + num::Bounded + Clone> Commutativity for Max<T> {}
+
+structure! {
+    /// Bitwise xor, every element is its own inverse.
+    BitXor
+}
+
+impl<T: num::PrimInt> Magma for BitXor<T> {
+    type Elem = T;
+
+    #[inline]
+    fn add(&self, lhs: T, rhs: T) -> T {
+        lhs ^ rhs
+    }
+}
+impl<T: num::PrimInt> Identity for BitXor<T> {
+    #[inline]
+    fn id(&self) -> T {
+        T::zero()
+    }
+}
+impl<T: num::PrimInt> Invertibillity for BitXor<T> {
+    #[inline]
+    fn inv(&self, x: T) -> T {
+        x
+    }
+}
+impl<T: num::PrimInt> Associativity for BitXor<T> {}
+impl<T: num::PrimInt> Commutativity for BitXor<T> {}
+
+structure! {
+    /// Bitwise and, whose identity has all the bits set.
+    BitAnd
+}
+
+impl<T: num::PrimInt> Magma for BitAnd<T> {
+    type Elem = T;
+
+    #[inline]
+    fn add(&self, lhs: T, rhs: T) -> T {
+        lhs & rhs
+    }
+}
+impl<T: num::PrimInt> Identity for BitAnd<T> {
+    #[inline]
+    fn id(&self) -> T {
+        !T::zero()
+    }
+}
+impl<T: num::PrimInt> Associativity for BitAnd<T> {}
+impl<T: num::PrimInt> Commutativity for BitAnd<T> {}
+
+structure! {
+    /// Bitwise or.
+    BitOr
+}
+
+impl<T: num::PrimInt> Magma for BitOr<T> {
+    type Elem = T;
+
+    #[inline]
+    fn add(&self, lhs: T, rhs: T) -> T {
+        lhs | rhs
+    }
+}
+impl<T: num::PrimInt> Identity for BitOr<T> {
+    #[inline]
+    fn id(&self) -> T {
+        T::zero()
+    }
+}
+impl<T: num::PrimInt> Associativity for BitOr<T> {}
+impl<T: num::PrimInt> Commutativity for BitOr<T> {}
+
+structure! {
+    /// The greatest common divisor, whose identity is zero. It's only a monoid over the
+    /// non-negative integers, because `gcd(0, -x) == x`.
+    Gcd
+}
+
+impl<T: num::Integer + Clone> Magma for Gcd<T> {
+    type Elem = T;
+
+    #[inline]
+    fn add(&self, lhs: T, rhs: T) -> T {
+        lhs.gcd(&rhs)
+    }
+}
+impl<T: num::Integer + Clone> Identity for Gcd<T> {
+    #[inline]
+    fn id(&self) -> T {
+        T::zero()
+    }
+}
+impl<T: num::Integer + Clone> Associativity for Gcd<T> {}
+impl<T: num::Integer + Clone> Commutativity for Gcd<T> {}
+
+structure! {
+    /// The least common multiple, whose identity is one.
+    Lcm
+}
+
+impl<T: num::Integer + Clone> Magma for Lcm<T> {
+    type Elem = T;
+
+    #[inline]
+    fn add(&self, lhs: T, rhs: T) -> T {
+        lhs.lcm(&rhs)
+    }
+}
+impl<T: num::Integer + Clone> Identity for Lcm<T> {
+    #[inline]
+    fn id(&self) -> T {
+        T::one()
+    }
+}
+impl<T: num::Integer + Clone> Associativity for Lcm<T> {}
+impl<T: num::Integer + Clone> Commutativity for Lcm<T> {}
+
+structure! {
+    /// The first `Some`, which isn't commutative.
+    First
+}
+
+impl<T: Clone> Magma for First<T> {
+    type Elem = Option<T>;
+
+    #[inline]
+    fn add(&self, lhs: Option<T>, rhs: Option<T>) -> Option<T> {
+        lhs.or(rhs)
+    }
+}
+impl<T: Clone> Identity for First<T> {
+    #[inline]
+    fn id(&self) -> Option<T> {
+        None
+    }
+}
+impl<T: Clone> Associativity for First<T> {}
+
+structure! {
+    /// The last `Some`, which isn't commutative.
+    Last
+}
+
+impl<T: Clone> Magma for Last<T> {
+    type Elem = Option<T>;
+
+    #[inline]
+    fn add(&self, lhs: Option<T>, rhs: Option<T>) -> Option<T> {
+        rhs.or(lhs)
+    }
+}
+impl<T: Clone> Identity for Last<T> {
+    #[inline]
+    fn id(&self) -> Option<T> {
+        None
+    }
+}
+impl<T: Clone> Associativity for Last<T> {}
+
+structure! {
+    /// Concatenation of `String`s or `Vec`s, which isn't commutative.
+    Concat
+}
+
+impl Magma for Concat<String> {
+    type Elem = String;
+
+    #[inline]
+    fn add(&self, mut lhs: String, rhs: String) -> String {
+        lhs.push_str(&rhs);
+        lhs
+    }
+}
+impl Identity for Concat<String> {
+    #[inline]
+    fn id(&self) -> String {
+        String::new()
+    }
+}
+impl Associativity for Concat<String> {}
+
+impl<T: Clone> Magma for Concat<Vec<T>> {
+    type Elem = Vec<T>;
+
+    #[inline]
+    fn add(&self, mut lhs: Vec<T>, rhs: Vec<T>) -> Vec<T> {
+        lhs.extend(rhs);
+        lhs
+    }
+}
+impl<T: Clone> Identity for Concat<Vec<T>> {
+    #[inline]
+    fn id(&self) -> Vec<T> {
+        Vec::new()
+    }
+}
+impl<T: Clone> Associativity for Concat<Vec<T>> {}
+
+structure! {
+    /// The minimum of `(value, index)` pairs, where ties go to the smaller index.
+    ArgMin
+}
+
+impl<T: Ord + Clone> Magma for ArgMin<T> {
+    type Elem = Option<(T, usize)>;
+
+    #[inline]
+    fn add(&self, lhs: Self::Elem, rhs: Self::Elem) -> Self::Elem {
+        match (lhs, rhs) {
+            (Some(lhs), Some(rhs)) => Some(lhs.min(rhs)),
+            (lhs, None) => lhs,
+            (None, rhs) => rhs,
+        }
+    }
+}
+impl<T: Ord + Clone> Identity for ArgMin<T> {
+    #[inline]
+    fn id(&self) -> Self::Elem {
+        None
+    }
+}
+impl<T: Ord + Clone> Associativity for ArgMin<T> {}
+impl<T: Ord + Clone> Commutativity for ArgMin<T> {}
+
+structure! {
+    /// The maximum of `(value, index)` pairs, where ties go to the smaller index.
+    ArgMax
+}
+
+impl<T: Ord + Clone> Magma for ArgMax<T> {
+    type Elem = Option<(T, usize)>;
+
+    #[inline]
+    fn add(&self, lhs: Self::Elem, rhs: Self::Elem) -> Self::Elem {
+        match (lhs, rhs) {
+            (Some((x, i)), Some((y, j))) => match x.cmp(&y) {
+                std::cmp::Ordering::Less => Some((y, j)),
+                std::cmp::Ordering::Greater => Some((x, i)),
+                std::cmp::Ordering::Equal => Some((x, i.min(j))),
+            },
+            (lhs, None) => lhs,
+            (None, rhs) => rhs,
+        }
+    }
+}
+impl<T: Ord + Clone> Identity for ArgMax<T> {
+    #[inline]
+    fn id(&self) -> Self::Elem {
+        None
+    }
+}
+impl<T: Ord + Clone> Associativity for ArgMax<T> {}
+impl<T: Ord + Clone> Commutativity for ArgMax<T> {}
+
+/// The product of two structures, which works on pairs of their elements. Tuples of up to six
+/// structures are products too.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Product<A, B>(pub A, pub B);
+
+impl<A, B> Product<A, B> {
+    pub const fn new(a: A, b: B) -> Self {
+        Self(a, b)
+    }
+}
+
+/// Implements the structure traits for a product type componentwise.
+macro_rules! product {
+    ($ty:ty; $($G:ident $i:tt),+) => {
+        impl<$($G: Magma),+> Magma for $ty {
+            type Elem = ($($G::Elem,)+);
+
+            #[inline]
+            fn add(&self, lhs: Self::Elem, rhs: Self::Elem) -> Self::Elem {
+                ($(self.$i.add(lhs.$i, rhs.$i),)+)
+            }
+        }
+        impl<$($G: Identity),+> Identity for $ty {
+            #[inline]
+            fn id(&self) -> Self::Elem {
+                ($(self.$i.id(),)+)
+            }
+        }
+        impl<$($G: Invertibillity),+> Invertibillity for $ty {
+            #[inline]
+            fn inv(&self, x: Self::Elem) -> Self::Elem {
+                ($(self.$i.inv(x.$i),)+)
+            }
+
+            #[inline]
+            fn sub(&self, lhs: Self::Elem, rhs: Self::Elem) -> Self::Elem {
+                ($(self.$i.sub(lhs.$i, rhs.$i),)+)
+            }
+        }
+        impl<$($G: Associativity),+> Associativity for $ty {}
+        impl<$($G: Commutativity),+> Commutativity for $ty {}
+        impl<$($G: ScalarMultiplication),+> ScalarMultiplication for $ty {
+            #[inline]
+            fn mul_scalar(&self, x: Self::Elem, n: usize) -> Self::Elem {
+                ($(self.$i.mul_scalar(x.$i, n),)+)
+            }
+        }
+    };
+}
+
+product!(Product<A, B>; A 0, B 1);
+product!((A, B); A 0, B 1);
+product!((A, B, C); A 0, B 1, C 2);
+product!((A, B, C, D); A 0, B 1, C 2, D 3);
+product!((A, B, C, D, E); A 0, B 1, C 2, D 3, E 4);
+product!((A, B, C, D, E, F); A 0, B 1, C 2, D 3, E 4, F 5);
+
+/// The structure `G` lifted to the elements `T`, which convert to and from `G::Elem`, like
+/// newtypes around them.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Mapped<G, T> {
+    group: G,
+    _marker: std::marker::PhantomData<T>,
+}
+
+impl<G, T> Mapped<G, T> {
+    pub const fn new(group: G) -> Self {
+        Self {
+            group,
+            _marker: std::marker::PhantomData,
+        }
+    }
+}
+
+impl<G: Magma, T: Clone + From<G::Elem> + Into<G::Elem>> Magma for Mapped<G, T> {
+    type Elem = T;
+
+    #[inline]
+    fn add(&self, lhs: T, rhs: T) -> T {
+        self.group.add(lhs.into(), rhs.into()).into()
+    }
+}
+impl<G: Identity, T: Clone + From<G::Elem> + Into<G::Elem>> Identity for Mapped<G, T> {
+    #[inline]
+    fn id(&self) -> T {
+        self.group.id().into()
+    }
+}
+impl<G: Invertibillity, T: Clone + From<G::Elem> + Into<G::Elem>> Invertibillity for Mapped<G, T> {
+    #[inline]
+    fn inv(&self, x: T) -> T {
+        self.group.inv(x.into()).into()
+    }
+
+    #[inline]
+    fn sub(&self, lhs: T, rhs: T) -> T {
+        self.group.sub(lhs.into(), rhs.into()).into()
+    }
+}
+impl<G: Associativity, T: Clone + From<G::Elem> + Into<G::Elem>> Associativity for Mapped<G, T> {}
+impl<G: Commutativity, T: Clone + From<G::Elem> + Into<G::Elem>> Commutativity for Mapped<G, T> {}
+impl<G: ScalarMultiplication, T: Clone + From<G::Elem> + Into<G::Elem>> ScalarMultiplication
+    for Mapped<G, T>
+{
+    #[inline]
+    fn mul_scalar(&self, x: T, n: usize) -> T {
+        self.group.mul_scalar(x.into(), n).into()
+    }
+}
+
+/// A monoid made of an identity and a closure, which has to be associative.
+#[derive(Clone, Copy)]
+pub struct FnMonoid<T, Op> {
+    id: T,
+    op: Op,
+}
+
+impl<T: Clone, Op: Fn(T, T) -> T> FnMonoid<T, Op> {
+    pub const fn new(id: T, op: Op) -> Self {
+        Self { id, op }
+    }
+
+    /// Marks the monoid as commutative, which is up to the caller to ensure.
+    pub const fn assume_commutative(self) -> Commutative<Self> {
+        Commutative(self)
+    }
+}
+
+impl<T: Clone, Op: Fn(T, T) -> T> Magma for FnMonoid<T, Op> {
+    type Elem = T;
+
+    #[inline]
+    fn add(&self, lhs: T, rhs: T) -> T {
+        (self.op)(lhs, rhs)
+    }
+}
+impl<T: Clone, Op: Fn(T, T) -> T> Identity for FnMonoid<T, Op> {
+    #[inline]
+    fn id(&self) -> T {
+        self.id.clone()
+    }
+}
+impl<T: Clone, Op: Fn(T, T) -> T> Associativity for FnMonoid<T, Op> {}
+
+/// A group made of an identity and closures, where `op` has to be associative and `inv` has to
+/// invert it.
+#[derive(Clone, Copy)]
+pub struct FnGroup<T, Op, Inv> {
+    id: T,
+    op: Op,
+    inv: Inv,
+}
+
+impl<T: Clone, Op: Fn(T, T) -> T, Inv: Fn(T) -> T> FnGroup<T, Op, Inv> {
+    pub const fn new(id: T, op: Op, inv: Inv) -> Self {
+        Self { id, op, inv }
+    }
+
+    /// Marks the group as commutative, which is up to the caller to ensure.
+    pub const fn assume_commutative(self) -> Commutative<Self> {
+        Commutative(self)
+    }
+}
+
+impl<T: Clone, Op: Fn(T, T) -> T, Inv: Fn(T) -> T> Magma for FnGroup<T, Op, Inv> {
+    type Elem = T;
+
+    #[inline]
+    fn add(&self, lhs: T, rhs: T) -> T {
+        (self.op)(lhs, rhs)
+    }
+}
+impl<T: Clone, Op: Fn(T, T) -> T, Inv: Fn(T) -> T> Identity for FnGroup<T, Op, Inv> {
+    #[inline]
+    fn id(&self) -> T {
+        self.id.clone()
+    }
+}
+impl<T: Clone, Op: Fn(T, T) -> T, Inv: Fn(T) -> T> Invertibillity for FnGroup<T, Op, Inv> {
+    #[inline]
+    fn inv(&self, x: T) -> T {
+        (self.inv)(x)
+    }
+}
+impl<T: Clone, Op: Fn(T, T) -> T, Inv: Fn(T) -> T> Associativity for FnGroup<T, Op, Inv> {}
+
+/// The structure `G` marked as commutative, see `FnMonoid::assume_commutative`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Commutative<G>(pub G);
+
+impl<G: Magma> Magma for Commutative<G> {
+    type Elem = G::Elem;
+
+    #[inline]
+    fn add(&self, lhs: G::Elem, rhs: G::Elem) -> G::Elem {
+        self.0.add(lhs, rhs)
+    }
+}
+impl<G: Identity> Identity for Commutative<G> {
+    #[inline]
+    fn id(&self) -> G::Elem {
+        self.0.id()
+    }
+}
+impl<G: Invertibillity> Invertibillity for Commutative<G> {
+    #[inline]
+    fn inv(&self, x: G::Elem) -> G::Elem {
+        self.0.inv(x)
+    }
+
+    #[inline]
+    fn sub(&self, lhs: G::Elem, rhs: G::Elem) -> G::Elem {
+        self.0.sub(lhs, rhs)
+    }
+}
+impl<G: Associativity> Associativity for Commutative<G> {}
+impl<G: Magma> Commutativity for Commutative<G> {}
+impl<G: ScalarMultiplication> ScalarMultiplication for Commutative<G> {
+    #[inline]
+    fn mul_scalar(&self, x: G::Elem, n: usize) -> G::Elem {
+        self.0.mul_scalar(x, n)
+    }
+}
+
+/// The semiring made of the additive structure `A` and the multiplicative structure `M`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct AddMul<A, M>(pub A, pub M);
+
+impl<A, M> AddMul<A, M> {
+    pub const fn new(additive: A, multiplicative: M) -> Self {
+        Self(additive, multiplicative)
+    }
+}
+
+impl<A: CommutativeMonoid, M: Monoid<Elem = A::Elem>> Semiring for AddMul<A, M> {
+    type Elem = A::Elem;
+    type Additive = A;
+    type Multiplicative = M;
+
+    #[inline]
+    fn additive(&self) -> &A {
+        &self.0
+    }
+
+    #[inline]
+    fn multiplicative(&self) -> &M {
+        &self.1
+    }
+}
+
+/// The usual addition and multiplication of numbers, which is a ring for signed numbers, the
+/// floats and `ModInt`. It implements `Field` for `ModInt` with any modulus, but it's a field only
+/// for a prime one, see `Field`.
+pub type NumRing<T> = AddMul<NumAdditiveGroups<T>, NumMultiplicative<T>>;
+
+/// The tropical (min,+) semiring, where `T::max_value()` is infinity.
+pub type MinPlus<T> = AddMul<Min<T>, InfAdd<T>>;
+
+/// The tropical (max,+) semiring, where `T::min_value()` is negative infinity.
+pub type MaxPlus<T> = AddMul<Max<T>, NegInfAdd<T>>;
+
+structure! {
+    /// Addition where `T::max_value()` is infinity, which absorbs every element. Sums saturate,
+    /// so it's associative only as long as they don't overflow.
+    InfAdd
+}
+
+impl<T: num::PrimInt> Magma for InfAdd<T> {
+    type Elem = T;
+
+    #[inline]
+    fn add(&self, lhs: T, rhs: T) -> T {
+        if lhs == T::max_value() || rhs == T::max_value() {
+            T::max_value()
+        } else {
+            lhs.saturating_add(rhs)
+        }
+    }
+}
+impl<T: num::PrimInt> Identity for InfAdd<T> {
+    #[inline]
+    fn id(&self) -> T {
+        T::zero()
+    }
+}
+impl<T: num::PrimInt> Associativity for InfAdd<T> {}
+impl<T: num::PrimInt> Commutativity for InfAdd<T> {}
+
+structure! {
+    /// Addition where `T::min_value()` is negative infinity, which absorbs every element. Sums
+    /// saturate, so it's associative only as long as they don't overflow.
+    NegInfAdd
+}
+
+impl<T: num::PrimInt> Magma for NegInfAdd<T> {
+    type Elem = T;
+
+    #[inline]
+    fn add(&self, lhs: T, rhs: T) -> T {
+        if lhs == T::min_value() || rhs == T::min_value() {
+            T::min_value()
+        } else {
+            lhs.saturating_add(rhs)
+        }
+    }
+}
+impl<T: num::PrimInt> Identity for NegInfAdd<T> {
+    #[inline]
+    fn id(&self) -> T {
+        T::zero()
+    }
+}
+impl<T: num::PrimInt> Associativity for NegInfAdd<T> {}
+impl<T: num::PrimInt> Commutativity for NegInfAdd<T> {}
@@ -0,0 +1,266 @@
+//! Checks on random samples that a structure obeys the laws its marker traits claim.
+
+use super::{Commutativity, Identity, Invertibillity, Magma, Semiring};
+use rand::Rng;
+use std::fmt;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Law {
+    /// `add(add(a, b), c) == add(a, add(b, c))`
+    Associativity,
+    /// `add(id(), a) == a == add(a, id())`
+    Identity,
+    /// `add(a, b) == add(b, a)`
+    Commutativity,
+    /// `add(a, inv(a)) == id() == add(inv(a), a)` and `sub(a, b) == add(a, inv(b))`
+    Inverse,
+    /// `mul(a, add(b, c)) == add(mul(a, b), mul(a, c))` and
+    /// `mul(add(a, b), c) == add(mul(a, c), mul(b, c))`
+    Distributivity,
+    /// `mul(zero(), a) == zero() == mul(a, zero())`
+    Annihilation,
+}
+
+/// The elements for which `law` doesn't hold.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Counterexample<T> {
+    pub law: Law,
+    pub elements: Vec<T>,
+}
+
+impl<T: fmt::Debug> fmt::Display for Counterexample<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?} doesn't hold for {:?}", self.law, self.elements)
+    }
+}
+
+pub type LawResult<T> = Result<(), Counterexample<T>>;
+
+/// Runs `check` on `samples` tuples of `N` elements of `gen`.
+fn check_samples<T: Clone, R: Rng, F: FnMut(&mut R) -> T, const N: usize>(
+    law: Law,
+    rng: &mut R,
+    mut gen: F,
+    samples: usize,
+    mut check: impl FnMut([T; N]) -> bool,
+) -> LawResult<T> {
+    for _ in 0..samples {
+        let elements: [T; N] = std::array::from_fn(|_| gen(rng));
+        if !check(elements.clone()) {
+            return Err(Counterexample {
+                law,
+                elements: elements.to_vec(),
+            });
+        }
+    }
+    Ok(())
+}
+
+pub fn check_associativity<G: Magma, R: Rng, F: FnMut(&mut R) -> G::Elem>(
+    group: &G,
+    rng: &mut R,
+    gen: F,
+    samples: usize,
+) -> LawResult<G::Elem>
+where
+    G::Elem: PartialEq,
+{
+    check_samples(Law::Associativity, rng, gen, samples, |[a, b, c]| {
+        let lhs = group.add(group.add(a.clone(), b.clone()), c.clone());
+        lhs == group.add(a, group.add(b, c))
+    })
+}
+
+pub fn check_identity<G: Identity, R: Rng, F: FnMut(&mut R) -> G::Elem>(
+    group: &G,
+    rng: &mut R,
+    gen: F,
+    samples: usize,
+) -> LawResult<G::Elem>
+where
+    G::Elem: PartialEq,
+{
+    check_samples(Law::Identity, rng, gen, samples, |[a]| {
+        group.add(group.id(), a.clone()) == a && group.add(a.clone(), group.id()) == a
+    })
+}
+
+pub fn check_commutativity<G: Magma, R: Rng, F: FnMut(&mut R) -> G::Elem>(
+    group: &G,
+    rng: &mut R,
+    gen: F,
+    samples: usize,
+) -> LawResult<G::Elem>
+where
+    G::Elem: PartialEq,
+{
+    check_samples(Law::Commutativity, rng, gen, samples, |[a, b]| {
+        group.add(a.clone(), b.clone()) == group.add(b, a)
+    })
+}
+
+pub fn check_inverse<G: Identity + Invertibillity, R: Rng, F: FnMut(&mut R) -> G::Elem>(
+    group: &G,
+    rng: &mut R,
+    gen: F,
+    samples: usize,
+) -> LawResult<G::Elem>
+where
+    G::Elem: PartialEq,
+{
+    check_samples(Law::Inverse, rng, gen, samples, |[a, b]| {
+        let inv = group.inv(a.clone());
+        group.add(a.clone(), inv.clone()) == group.id()
+            && group.add(inv, a.clone()) == group.id()
+            && group.sub(a.clone(), b.clone()) == group.add(a, group.inv(b))
+    })
+}
+
+/// Checks the laws of `Monoid`.
+pub fn check_monoid<G: super::Monoid, R: Rng, F: FnMut(&mut R) -> G::Elem>(
+    group: &G,
+    rng: &mut R,
+    mut gen: F,
+    samples: usize,
+) -> LawResult<G::Elem>
+where
+    G::Elem: PartialEq,
+{
+    check_associativity(group, rng, &mut gen, samples)?;
+    check_identity(group, rng, &mut gen, samples)
+}
+
+/// Checks the laws of `CommutativeMonoid`.
+pub fn check_commutative_monoid<G: super::CommutativeMonoid, R: Rng, F: FnMut(&mut R) -> G::Elem>(
+    group: &G,
+    rng: &mut R,
+    mut gen: F,
+    samples: usize,
+) -> LawResult<G::Elem>
+where
+    G::Elem: PartialEq,
+{
+    check_monoid(group, rng, &mut gen, samples)?;
+    check_commutativity(group, rng, &mut gen, samples)
+}
+
+/// Checks the laws of `Group`.
+pub fn check_group<G: super::Group, R: Rng, F: FnMut(&mut R) -> G::Elem>(
+    group: &G,
+    rng: &mut R,
+    mut gen: F,
+    samples: usize,
+) -> LawResult<G::Elem>
+where
+    G::Elem: PartialEq,
+{
+    check_monoid(group, rng, &mut gen, samples)?;
+    check_inverse(group, rng, &mut gen, samples)
+}
+
+/// Checks the laws of `AbelianGroup`.
+pub fn check_abelian_group<G: super::AbelianGroup, R: Rng, F: FnMut(&mut R) -> G::Elem>(
+    group: &G,
+    rng: &mut R,
+    mut gen: F,
+    samples: usize,
+) -> LawResult<G::Elem>
+where
+    G::Elem: PartialEq,
+{
+    check_group(group, rng, &mut gen, samples)?;
+    check_commutativity(group, rng, &mut gen, samples)
+}
+
+pub fn check_distributivity<S: Semiring, R: Rng, F: FnMut(&mut R) -> S::Elem>(
+    semiring: &S,
+    rng: &mut R,
+    gen: F,
+    samples: usize,
+) -> LawResult<S::Elem>
+where
+    S::Elem: PartialEq,
+{
+    let s = semiring;
+    check_samples(Law::Distributivity, rng, gen, samples, |[a, b, c]| {
+        let left = s.mul(a.clone(), s.add(b.clone(), c.clone()))
+            == s.add(s.mul(a.clone(), b.clone()), s.mul(a.clone(), c.clone()));
+        let right = s.mul(s.add(a.clone(), b.clone()), c.clone())
+            == s.add(s.mul(a, c.clone()), s.mul(b, c));
+        left && right
+    })
+}
+
+pub fn check_annihilation<S: Semiring, R: Rng, F: FnMut(&mut R) -> S::Elem>(
+    semiring: &S,
+    rng: &mut R,
+    gen: F,
+    samples: usize,
+) -> LawResult<S::Elem>
+where
+    S::Elem: PartialEq,
+{
+    let s = semiring;
+    check_samples(Law::Annihilation, rng, gen, samples, |[a]| {
+        s.mul(s.zero(), a.clone()) == s.zero() && s.mul(a, s.zero()) == s.zero()
+    })
+}
+
+/// Checks the laws of `Semiring`, including those of its parts.
+pub fn check_semiring<S: Semiring, R: Rng, F: FnMut(&mut R) -> S::Elem>(
+    semiring: &S,
+    rng: &mut R,
+    mut gen: F,
+    samples: usize,
+) -> LawResult<S::Elem>
+where
+    S::Elem: PartialEq,
+{
+    check_commutative_monoid(semiring.additive(), rng, &mut gen, samples)?;
+    check_monoid(semiring.multiplicative(), rng, &mut gen, samples)?;
+    check_distributivity(semiring, rng, &mut gen, samples)?;
+    check_annihilation(semiring, rng, &mut gen, samples)
+}
+
+/// Checks the laws of `Ring`, including those of its parts.
+pub fn check_ring<S: Semiring, R: Rng, F: FnMut(&mut R) -> S::Elem>(
+    ring: &S,
+    rng: &mut R,
+    mut gen: F,
+    samples: usize,
+) -> LawResult<S::Elem>
+where
+    S::Additive: Invertibillity,
+    S::Elem: PartialEq,
+{
+    check_semiring(ring, rng, &mut gen, samples)?;
+    check_inverse(ring.additive(), rng, &mut gen, samples)
+}
+
+/// Checks the laws of `Field`, including those of its parts. The multiplicative inverse is only
+/// checked for the nonzero samples.
+pub fn check_field<S: Semiring, R: Rng, F: FnMut(&mut R) -> S::Elem>(
+    field: &S,
+    rng: &mut R,
+    mut gen: F,
+    samples: usize,
+) -> LawResult<S::Elem>
+where
+    S::Additive: Invertibillity,
+    S::Multiplicative: Invertibillity + Commutativity,
+    S::Elem: PartialEq,
+{
+    check_ring(field, rng, &mut gen, samples)?;
+    check_commutativity(field.multiplicative(), rng, &mut gen, samples)?;
+
+    let m = field.multiplicative();
+    check_samples(Law::Inverse, rng, gen, samples, |[a, b]| {
+        if a == field.zero() || b == field.zero() {
+            return true;
+        }
+        let inv = m.inv(a.clone());
+        m.add(a.clone(), inv.clone()) == m.id()
+            && m.add(inv, a.clone()) == m.id()
+            && m.sub(a.clone(), b.clone()) == m.add(a, m.inv(b))
+    })
+}
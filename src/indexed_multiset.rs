@@ -0,0 +1,158 @@
+use crate::{groups::NumAdditiveGroups, FenwickTree};
+use std::iter::FusedIterator;
+
+/// A multiset of the integers in `0..universe`, with order statistics.
+#[derive(Clone, Debug)]
+pub struct IndexedMultiset {
+    len: usize,
+    /// The number of occurrences of each integer.
+    counts: FenwickTree<NumAdditiveGroups<usize>>,
+}
+
+impl IndexedMultiset {
+    /// O(U)
+    pub fn new(universe: usize) -> Self {
+        let mut counts = FenwickTree::with_capacity(NumAdditiveGroups::new(), universe);
+        counts.resize_id(universe);
+        Self { len: 0, counts }
+    }
+
+    /// The number of elements, counted with multiplicity.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The integers which can be inserted are `0..universe()`.
+    #[inline]
+    pub fn universe(&self) -> usize {
+        self.counts.len()
+    }
+
+    /// O(log U)
+    pub fn insert(&mut self, x: usize) {
+        assert!(
+            x < self.universe(),
+            "{} is out of the universe 0..{}",
+            x,
+            self.universe()
+        );
+        self.counts.update_add(x, 1);
+        self.len += 1;
+    }
+
+    /// Removes one occurrence of `x`, and returns whether there was one.
+    ///
+    /// O(log U)
+    pub fn remove(&mut self, x: usize) -> bool {
+        if self.count(x) == 0 {
+            return false;
+        }
+        self.counts.update_sub(x, 1);
+        self.len -= 1;
+        true
+    }
+
+    /// The number of occurrences of `x`.
+    ///
+    /// O(log U)
+    pub fn count(&self, x: usize) -> usize {
+        if x < self.universe() {
+            self.counts.prefix_sum(x + 1) - self.counts.prefix_sum(x)
+        } else {
+            0
+        }
+    }
+
+    #[inline]
+    pub fn contains(&self, x: usize) -> bool {
+        self.count(x) != 0
+    }
+
+    /// The number of elements smaller than `x`.
+    ///
+    /// O(log U)
+    pub fn rank(&self, x: usize) -> usize {
+        self.counts.prefix_sum(x.min(self.universe()))
+    }
+
+    /// The `k`th (from 0) smallest element, or `None` if there're at most `k` elements.
+    ///
+    /// O(log U)
+    pub fn kth(&self, k: usize) -> Option<usize> {
+        if self.len <= k {
+            return None;
+        }
+        // The smallest `i` such that there're more than `k` elements in `0..i`.
+        self.counts.lower_bound(&(k + 1)).map(|i| i - 1)
+    }
+
+    /// Iterates over the elements in order, with multiplicity.
+    pub fn iter(&self) -> IndexedMultisetIter<'_> {
+        IndexedMultisetIter {
+            set: self,
+            front: 0,
+            back: self.len,
+        }
+    }
+}
+
+impl Extend<usize> for IndexedMultiset {
+    fn extend<Iter: IntoIterator<Item = usize>>(&mut self, iter: Iter) {
+        for x in iter {
+            self.insert(x);
+        }
+    }
+}
+
+impl<'a> IntoIterator for &'a IndexedMultiset {
+    type Item = usize;
+    type IntoIter = IndexedMultisetIter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Each step is a `kth` query, so it's O(log U).
+#[derive(Clone, Debug)]
+pub struct IndexedMultisetIter<'a> {
+    set: &'a IndexedMultiset,
+    front: usize,
+    back: usize,
+}
+
+impl<'a> Iterator for IndexedMultisetIter<'a> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        if self.front == self.back {
+            return None;
+        }
+        self.front += 1;
+        self.set.kth(self.front - 1)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.back - self.front;
+        (len, Some(len))
+    }
+}
+
+impl<'a> DoubleEndedIterator for IndexedMultisetIter<'a> {
+    fn next_back(&mut self) -> Option<usize> {
+        if self.front == self.back {
+            return None;
+        }
+        self.back -= 1;
+        self.set.kth(self.back)
+    }
+}
+
+impl<'a> ExactSizeIterator for IndexedMultisetIter<'a> {}
+impl<'a> FusedIterator for IndexedMultisetIter<'a> {}
@@ -0,0 +1,209 @@
+use crate::{
+    groups::{Monoid, MonoidAction},
+    segment_tree::bounds,
+};
+use std::ops::RangeBounds;
+
+#[derive(Clone, Debug)]
+pub struct LazySegmentTree<M: Monoid, A: MonoidAction<M>> {
+    monoid: M,
+    action: A,
+    len: usize,
+    data: Vec<M::Elem>,
+    lazy: Vec<A::Elem>,
+}
+
+impl<M: Monoid, A: MonoidAction<M>> LazySegmentTree<M, A> {
+    #[inline]
+    pub fn new(monoid: M, action: A, length: usize) -> Self {
+        let mut data = Vec::new();
+        data.resize_with(4 * length, || monoid.id());
+        let mut lazy = Vec::new();
+        lazy.resize_with(4 * length, || action.id());
+
+        Self {
+            monoid,
+            action,
+            len: length,
+            data,
+            lazy,
+        }
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Applies `action` to the node, which covers a segment of `len` elements.
+    fn apply_node(&mut self, node: usize, len: usize, action: A::Elem) {
+        self.data[node] = self.action.act(action.clone(), self.data[node].clone());
+        if 1 < len {
+            self.lazy[node] = self.action.add(action, self.lazy[node].clone());
+        }
+    }
+
+    /// Pushes the pending action of the node down to its children.
+    fn push(&mut self, node: usize, lo: usize, hi: usize) {
+        let mid = (lo + hi) / 2;
+        let action = std::mem::replace(&mut self.lazy[node], self.action.id());
+        self.apply_node(2 * node + 1, mid - lo, action.clone());
+        self.apply_node(2 * node + 2, hi - mid, action);
+    }
+
+    fn pull(&mut self, node: usize) {
+        self.data[node] = self.monoid.add(
+            self.data[2 * node + 1].clone(),
+            self.data[2 * node + 2].clone(),
+        );
+    }
+
+    /// Sets the first elements to the elements of `iter`.
+    ///
+    /// O(n)
+    pub fn build<Iter: IntoIterator<Item = M::Elem>>(&mut self, iter: Iter) {
+        fn build_rec<M: Monoid, A: MonoidAction<M>, Iter: Iterator<Item = M::Elem>>(
+            slf: &mut LazySegmentTree<M, A>,
+            node: usize,
+            lo: usize,
+            hi: usize,
+            iter: &mut Iter,
+        ) {
+            if hi - lo == 1 {
+                if let Some(x) = iter.next() {
+                    slf.data[node] = x;
+                }
+            } else {
+                let mid = (lo + hi) / 2;
+                slf.push(node, lo, hi);
+                build_rec(slf, 2 * node + 1, lo, mid, iter);
+                build_rec(slf, 2 * node + 2, mid, hi, iter);
+                slf.pull(node);
+            }
+        }
+
+        if !self.is_empty() {
+            build_rec(self, 0, 0, self.len(), &mut iter.into_iter());
+        }
+    }
+
+    /// Applies `action` to every element in `range`.
+    ///
+    /// O(log n)
+    pub fn apply<R: RangeBounds<usize>>(&mut self, range: R, action: A::Elem) {
+        fn apply_rec<M: Monoid, A: MonoidAction<M>>(
+            slf: &mut LazySegmentTree<M, A>,
+            node: usize,
+            lo: usize,
+            hi: usize,
+            l: usize,
+            r: usize,
+            action: &A::Elem,
+        ) {
+            if r <= lo || hi <= l {
+                return;
+            }
+            if l <= lo && hi <= r {
+                slf.apply_node(node, hi - lo, action.clone());
+                return;
+            }
+
+            let mid = (lo + hi) / 2;
+            slf.push(node, lo, hi);
+            apply_rec(slf, 2 * node + 1, lo, mid, l, r, action);
+            apply_rec(slf, 2 * node + 2, mid, hi, l, r, action);
+            slf.pull(node);
+        }
+
+        let (l, r) = bounds(range, self.len());
+        if l < r {
+            apply_rec(self, 0, 0, self.len(), l, r, &action);
+        }
+    }
+
+    /// Sums the elements in `range` in order.
+    ///
+    /// O(log n)
+    pub fn sum<R: RangeBounds<usize>>(&self, range: R) -> M::Elem {
+        fn sum_rec<M: Monoid, A: MonoidAction<M>>(
+            slf: &LazySegmentTree<M, A>,
+            node: usize,
+            lo: usize,
+            hi: usize,
+            l: usize,
+            r: usize,
+        ) -> M::Elem {
+            if r <= lo || hi <= l {
+                return slf.monoid.id();
+            }
+            if l <= lo && hi <= r {
+                return slf.data[node].clone();
+            }
+
+            // The pending action of `node` isn't pushed, so it's applied to the sum of the children.
+            let mid = (lo + hi) / 2;
+            let s = slf.monoid.add(
+                sum_rec(slf, 2 * node + 1, lo, mid, l, r),
+                sum_rec(slf, 2 * node + 2, mid, hi, l, r),
+            );
+            slf.action.act(slf.lazy[node].clone(), s)
+        }
+
+        let (l, r) = bounds(range, self.len());
+        if l < r {
+            sum_rec(self, 0, 0, self.len(), l, r)
+        } else {
+            self.monoid.id()
+        }
+    }
+
+    /// O(log n)
+    pub fn update(&mut self, i: usize, x: M::Elem) {
+        fn update_rec<M: Monoid, A: MonoidAction<M>>(
+            slf: &mut LazySegmentTree<M, A>,
+            node: usize,
+            lo: usize,
+            hi: usize,
+            i: usize,
+            x: M::Elem,
+        ) {
+            if hi - lo == 1 {
+                slf.data[node] = x;
+                return;
+            }
+
+            let mid = (lo + hi) / 2;
+            slf.push(node, lo, hi);
+            if i < mid {
+                update_rec(slf, 2 * node + 1, lo, mid, i, x);
+            } else {
+                update_rec(slf, 2 * node + 2, mid, hi, i, x);
+            }
+            slf.pull(node);
+        }
+
+        assert!(
+            i < self.len(),
+            "index {} is out of bounds for length {}",
+            i,
+            self.len()
+        );
+        update_rec(self, 0, 0, self.len(), i, x);
+    }
+
+    /// O(log n)
+    pub fn get(&self, i: usize) -> M::Elem {
+        assert!(
+            i < self.len(),
+            "index {} is out of bounds for length {}",
+            i,
+            self.len()
+        );
+        self.sum(i..=i)
+    }
+}
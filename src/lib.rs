@@ -1,31 +1,56 @@
 pub mod groups;
 
+mod beats_segment_tree;
 mod btree;
 mod complex;
+mod compressed_fenwick_tree;
 mod fenwick_tree;
+mod fenwick_tree_2d;
 mod fft;
 mod heap;
 mod huffman;
-// mod ord_btree;
+mod indexed_multiset;
+mod lazy_segment_tree;
+mod mod_int;
+mod ord_btree;
+mod persistent_segment_tree;
+mod range_fenwick_tree;
 mod ref_stack;
 mod segment_tree;
+mod segment_tree_2d;
+mod sparse_segment_tree;
 mod stack_vec;
 mod union_find;
+mod wavelet_matrix;
 
 // use complex::Complex64;
 use fft::{eval_poly, fft2, ifft2, PrintPoly};
 // use huffman::Huffman;
 
+pub use beats_segment_tree::BeatsSegmentTree;
 pub use btree::BTree;
+pub use compressed_fenwick_tree::CompressedFenwickTree;
 pub use fenwick_tree::FenwickTree;
+pub use fenwick_tree_2d::FenwickTree2D;
 pub use heap::{MaxHeap, MinHeap};
-// pub use ord_btree::{OrdBTree, OrdSize, OrdSizeOne}; // , RefMutBTreeElement};
+pub use indexed_multiset::{IndexedMultiset, IndexedMultisetIter};
+pub use lazy_segment_tree::LazySegmentTree;
+pub use mod_int::{ConstModulus, DynModInt, DynModulus, ModInt, Modular, Modulus, Montgomery};
+pub use ord_btree::{
+    BTreeDrain, BTreeRange, OrdBTree, OrdDimension, OrdSize, OrdSizeOne, OrdSummary, TextDimension,
+    TextSummary,
+}; // , RefMutBTreeElement};
+pub use persistent_segment_tree::{PersistentSegmentTree, SegmentTreeVersion};
+pub use range_fenwick_tree::RangeFenwickTree;
 pub use ref_stack::{OnStackRefMutStack, RefMutStack};
 pub use segment_tree::SegmentTree;
+pub use segment_tree_2d::SegmentTree2D;
+pub use sparse_segment_tree::SparseSegmentTree;
 pub use stack_vec::{
     OuterLenStackVec, OuterLenStackVecDrain, StackVec, StackVecDrain, StackVecIntoIter,
 };
 pub use union_find::UnionFind;
+pub use wavelet_matrix::WaveletMatrix;
 
 use rand::prelude::*;
 
@@ -76,6 +101,9 @@ fn bench_prefix_sum_dstruct() {
 
     bench("SegmentTree::build", 1, || st.build(a.iter().cloned()));
     bench("FenwickTree::extend", 1, || ft.extend(a.iter().cloned()));
+    bench("FenwickTree::from_vec", 1, || {
+        FenwickTree::from_vec(groups::NumAdditiveGroups::<i32>::new(), a.clone());
+    });
     println!();
 
     bench("SegmentTree::prefix_sum", 1, || {
@@ -163,6 +191,1410 @@ fn bench_fft_dstruct() {
     );
 }
 
+#[allow(dead_code)]
+fn validate_segment_tree_dstruct() {
+    use groups::{Associativity, Identity, Magma};
+
+    const P: u64 = 998_244_353;
+
+    /// Composition of the affine maps `x -> a * x + b` modulo `P`, the left map is applied first.
+    struct Affine;
+    impl Magma for Affine {
+        type Elem = (u64, u64);
+        fn add(&self, (a1, b1): (u64, u64), (a2, b2): (u64, u64)) -> (u64, u64) {
+            (a2 * a1 % P, (a2 * b1 + b2) % P)
+        }
+    }
+    impl Identity for Affine {
+        fn id(&self) -> (u64, u64) {
+            (1, 0)
+        }
+    }
+    impl Associativity for Affine {}
+
+    let mut rng = SmallRng::from_entropy();
+
+    println!("[Validate SegmentTree]");
+    for _ in 0..16 {
+        let n = rng.gen_range(1..128);
+        let mut true_a: Vec<_> = (0..n)
+            .map(|_| (rng.gen_range(0..P), rng.gen_range(0..P)))
+            .collect();
+
+        let mut st = SegmentTree::new(Affine, n);
+        st.build(true_a.iter().cloned());
+
+        for _ in 0..n {
+            let i = rng.gen_range(0..n);
+            let x = (rng.gen_range(0..P), rng.gen_range(0..P));
+            st.update(i, x);
+            true_a[i] = x;
+
+            for l in 0..=n {
+                let mut true_sum = Affine.id();
+                assert_eq!(st.sum(l, l), true_sum);
+                for (r, &x) in true_a.iter().enumerate().skip(l) {
+                    true_sum = Affine.add(true_sum, x);
+                    assert_eq!(st.sum(l, r + 1), true_sum);
+                }
+            }
+        }
+    }
+    println!("SegmentTree VALIDATED");
+    println!();
+}
+
+#[allow(dead_code)]
+fn validate_segment_tree_resize_dstruct() {
+    let mut rng = SmallRng::from_entropy();
+
+    println!("[Validate SegmentTree push/pop]");
+    for _ in 0..16 {
+        let n = rng.gen_range(0..64);
+        let mut true_a: Vec<i64> = (0..n).map(|_| rng.gen_range(-1000..1000)).collect();
+        let mut st = SegmentTree::from_iter_in(groups::NumAdditiveGroups::new(), true_a.clone());
+        let mut max_len = n;
+
+        for _ in 0..256 {
+            match rng.gen_range(0..8) {
+                0..=3 => {
+                    let x = rng.gen_range(-1000..1000);
+                    st.push(x);
+                    true_a.push(x);
+                }
+                4 | 5 => assert_eq!(st.pop(), true_a.pop()),
+                6 => {
+                    let size = rng.gen_range(0..2 * true_a.len() + 2);
+                    st.resize_with(size, || 1);
+                    true_a.resize(size, 1);
+                }
+                _ => {
+                    let xs: Vec<i64> = (0..rng.gen_range(0..16))
+                        .map(|_| rng.gen_range(-1000..1000))
+                        .collect();
+                    if rng.gen() {
+                        // An exact size hint, so `extend` reserves room for all of `xs` up front.
+                        st.extend(xs.iter().copied());
+                    } else {
+                        // `filter` has a lower bound of 0, so `extend` reserves nothing and the
+                        // pushes have to grow the tree.
+                        st.extend(xs.iter().copied().filter(|_| true));
+                    }
+                    true_a.extend(xs);
+                }
+            }
+            assert_eq!(st.len(), true_a.len());
+            max_len = max_len.max(true_a.len());
+            assert!(st.len() <= st.capacity() && st.capacity() <= 2 * max_len + 1);
+
+            let l = rng.gen_range(0..=true_a.len());
+            let r = rng.gen_range(l..=true_a.len());
+            assert_eq!(st.sum(l, r), true_a[l..r].iter().sum::<i64>());
+            assert_eq!(st.sum(0, true_a.len()), true_a.iter().sum::<i64>());
+            assert_eq!(st.max_right(l, |_| true), true_a.len());
+        }
+    }
+    println!("SegmentTree push/pop VALIDATED");
+    println!();
+}
+
+#[allow(dead_code)]
+fn validate_segment_tree_query_dstruct() {
+    use std::ops::Bound;
+
+    let mut rng = SmallRng::from_entropy();
+
+    println!("[Validate SegmentTree::query]");
+    for _ in 0..16 {
+        let n = rng.gen_range(1..128);
+        let mut true_a: Vec<i64> = (0..n).map(|_| rng.gen_range(-1000..1000)).collect();
+        let mut st = SegmentTree::from_iter_in(groups::NumAdditiveGroups::new(), true_a.clone());
+
+        for _ in 0..256 {
+            let i = rng.gen_range(0..n);
+            let dx = rng.gen_range(-1000..1000);
+            st.modify(i, |x| *x += dx);
+            true_a[i] += dx;
+            assert_eq!(st.as_slice(), &true_a[..]);
+
+            let l = rng.gen_range(0..=n);
+            let r = rng.gen_range(l..=n);
+            let true_sum = true_a[l..r].iter().sum::<i64>();
+            assert_eq!(st.query(l..r), true_sum);
+            assert_eq!(st.query(..r), true_a[..r].iter().sum::<i64>());
+            if l < r {
+                assert_eq!(
+                    st.query((Bound::Excluded(l), Bound::Unbounded)),
+                    true_a[l + 1..].iter().sum::<i64>()
+                );
+                assert_eq!(st.query(l..=r - 1), true_sum);
+            }
+            assert_eq!(st.try_query(l..r), Some(true_sum));
+            assert_eq!(st.try_query(l..n + 1), None);
+            assert_eq!(st.try_query(r + 1..r), None);
+        }
+        assert_eq!(st.try_query(..=usize::MAX), None);
+        assert_eq!(
+            st.try_query((Bound::Excluded(usize::MAX), Bound::Unbounded)),
+            None
+        );
+    }
+    println!("SegmentTree::query VALIDATED");
+    println!();
+}
+
+#[allow(dead_code)]
+fn validate_segment_tree_search_dstruct() {
+    let mut rng = SmallRng::from_entropy();
+
+    println!("[Validate SegmentTree max_right/min_left]");
+    for _ in 0..64 {
+        let n = rng.gen_range(1..256);
+        let a: Vec<u64> = (0..n).map(|_| rng.gen_range(0..16)).collect();
+
+        let mut st = SegmentTree::new(groups::NumAdditiveGroups::<u64>::new(), n);
+        st.build(a.iter().cloned());
+
+        for _ in 0..64 {
+            let cap = rng.gen_range(0..16 * n as u64);
+
+            let l = rng.gen_range(0..=n);
+            let mut true_r = l;
+            let mut sum = 0;
+            while true_r < n && sum + a[true_r] <= cap {
+                sum += a[true_r];
+                true_r += 1;
+            }
+            assert_eq!(st.max_right(l, |&s| s <= cap), true_r);
+
+            let r = rng.gen_range(0..=n);
+            let mut true_l = r;
+            let mut sum = 0;
+            while 0 < true_l && sum + a[true_l - 1] <= cap {
+                sum += a[true_l - 1];
+                true_l -= 1;
+            }
+            assert_eq!(st.min_left(r, |&s| s <= cap), true_l);
+        }
+    }
+    println!("SegmentTree max_right/min_left VALIDATED");
+    println!();
+}
+
+#[allow(dead_code)]
+fn validate_sparse_segment_tree_dstruct() {
+    use std::collections::BTreeMap;
+
+    let mut rng = SmallRng::from_entropy();
+
+    println!("[Validate SparseSegmentTree]");
+    for &(lo, hi) in &[(i64::MIN, i64::MAX), (-1000, 1000), (0, 1)] {
+        let mut st = SparseSegmentTree::new(groups::NumAdditiveGroups::<i64>::new(), lo, hi);
+        let mut true_a = BTreeMap::new();
+
+        for _ in 0..1024 {
+            let i = rng.gen_range(lo..hi);
+            let x = rng.gen_range(-1000..1000);
+            st.update(i, x);
+            true_a.insert(i, x);
+
+            let i = match true_a.keys().choose(&mut rng) {
+                Some(&i) if rng.gen() => i,
+                _ => rng.gen_range(lo..hi),
+            };
+            assert_eq!(*st.get(i), true_a.get(&i).copied().unwrap_or(0));
+
+            let (mut l, mut r) = (rng.gen_range(lo..=hi), rng.gen_range(lo..=hi));
+            if r < l {
+                std::mem::swap(&mut l, &mut r);
+            }
+            assert_eq!(
+                st.sum(l, r),
+                true_a.range(l..r).map(|(_, &x)| x).sum::<i64>()
+            );
+        }
+        assert_eq!(st.sum(lo, hi), true_a.values().sum::<i64>());
+    }
+
+    let mut st = SparseSegmentTree::new(groups::NumAdditiveGroups::<u64>::new(), 0, u64::MAX);
+    st.update(u64::MAX - 1, 1);
+    st.update(0, 2);
+    st.update(1 << 63, 4);
+    assert_eq!(st.sum(0, u64::MAX), 7);
+    assert_eq!(st.sum(1, u64::MAX - 1), 4);
+    assert_eq!(*st.get(u64::MAX - 1), 1);
+    assert!(st.num_nodes() <= 1 + 3 * 64);
+
+    println!("SparseSegmentTree VALIDATED");
+    println!();
+}
+
+#[allow(dead_code)]
+fn validate_persistent_segment_tree_dstruct() {
+    let mut rng = SmallRng::from_entropy();
+
+    println!("[Validate PersistentSegmentTree]");
+    for _ in 0..16 {
+        let n = rng.gen_range(1..128);
+        let mut st = PersistentSegmentTree::new(groups::NumAdditiveGroups::<i64>::new(), n);
+
+        let first: Vec<i64> = (0..n).map(|_| rng.gen_range(-1000..1000)).collect();
+        let mut versions = vec![(st.build(first.iter().cloned()), first)];
+        versions.push((st.empty(), vec![0; n]));
+
+        for _ in 0..4 * n {
+            let (version, mut true_a) = versions.choose(&mut rng).unwrap().clone();
+            let i = rng.gen_range(0..n);
+            let x = rng.gen_range(-1000..1000);
+            true_a[i] = x;
+            versions.push((st.update(version, i, x), true_a));
+
+            let (version, true_a) = versions.choose(&mut rng).unwrap();
+            let l = rng.gen_range(0..=n);
+            let r = rng.gen_range(l..=n);
+            assert_eq!(st.sum(*version, l, r), true_a[l..r].iter().sum::<i64>());
+            assert_eq!(*st.get(*version, i), true_a[i]);
+        }
+    }
+
+    // k-th smallest in a range.
+    for _ in 0..16 {
+        let n = rng.gen_range(1..256);
+        let a: Vec<u32> = (0..n).map(|_| rng.gen_range(0..64)).collect();
+
+        let mut values = a.clone();
+        values.sort_unstable();
+        values.dedup();
+
+        let mut st =
+            PersistentSegmentTree::new(groups::NumAdditiveGroups::<i32>::new(), values.len());
+        let mut prefixes = vec![st.empty()];
+        for x in &a {
+            let i = values.binary_search(x).unwrap();
+            let version = st.update_add(*prefixes.last().unwrap(), i, 1);
+            prefixes.push(version);
+        }
+
+        for _ in 0..256 {
+            let l = rng.gen_range(0..=n);
+            let r = rng.gen_range(l..=n);
+            let mut sorted = a[l..r].to_vec();
+            sorted.sort_unstable();
+
+            let k = rng.gen_range(0..=r - l);
+            let kth = st.kth(prefixes[l], prefixes[r], k as i32);
+            assert_eq!(kth.map(|i| values[i]), sorted.get(k).copied());
+        }
+    }
+    println!("PersistentSegmentTree VALIDATED");
+    println!();
+}
+
+#[allow(dead_code)]
+fn validate_2d_trees_dstruct() {
+    let mut rng = SmallRng::from_entropy();
+
+    println!("[Validate SegmentTree2D and FenwickTree2D]");
+    for _ in 0..16 {
+        let (w, h) = (rng.gen_range(1..32), rng.gen_range(1..32));
+        let mut st = SegmentTree2D::new(groups::NumAdditiveGroups::<i64>::new(), w, h);
+        let mut ft = FenwickTree2D::new(groups::NumAdditiveGroups::<i64>::new(), w, h);
+        let mut true_a = vec![vec![0; h]; w];
+
+        for _ in 0..256 {
+            let (x, y) = (rng.gen_range(0..w), rng.gen_range(0..h));
+            let v = rng.gen_range(-1000..1000);
+            st.update(x, y, v);
+            if rng.gen() {
+                ft.update_set(x, y, v);
+            } else {
+                ft.update_add(x, y, v - true_a[x][y]);
+            }
+            true_a[x][y] = v;
+
+            let (x0, y0) = (rng.gen_range(0..=w), rng.gen_range(0..=h));
+            let (x1, y1) = (rng.gen_range(x0..=w), rng.gen_range(y0..=h));
+            let true_sum: i64 = true_a[x0..x1]
+                .iter()
+                .map(|row| row[y0..y1].iter().sum::<i64>())
+                .sum();
+            assert_eq!(st.sum(x0..x1, y0..y1), true_sum);
+            assert_eq!(ft.sum(x0..x1, y0..y1), true_sum);
+
+            let (x, y) = (rng.gen_range(0..w), rng.gen_range(0..h));
+            assert_eq!(*st.get(x, y), true_a[x][y]);
+            assert_eq!(ft.get(x, y), true_a[x][y]);
+        }
+    }
+
+    for (w, h) in [(0, 0), (0, 5), (5, 0)] {
+        let st = SegmentTree2D::new(groups::NumAdditiveGroups::<i64>::new(), w, h);
+        let ft = FenwickTree2D::new(groups::NumAdditiveGroups::<i64>::new(), w, h);
+        assert_eq!(st.sum(0..w, 0..h), 0);
+        assert_eq!(ft.sum(0..w, 0..h), 0);
+    }
+    println!("SegmentTree2D and FenwickTree2D VALIDATED");
+    println!();
+}
+
+#[allow(dead_code)]
+fn validate_beats_segment_tree_dstruct() {
+    let mut rng = SmallRng::from_entropy();
+
+    println!("[Validate BeatsSegmentTree]");
+    for _ in 0..64 {
+        let n = rng.gen_range(1..128);
+        let mut true_a: Vec<i64> = (0..n).map(|_| rng.gen_range(-100..100)).collect();
+        let mut st = BeatsSegmentTree::new(n);
+        st.build(true_a.iter().cloned());
+
+        for _ in 0..256 {
+            let l = rng.gen_range(0..=n);
+            let r = rng.gen_range(l..=n);
+            let x = rng.gen_range(-100..100);
+            match rng.gen_range(0..3) {
+                0 => {
+                    st.chmin(l..r, x);
+                    true_a[l..r].iter_mut().for_each(|a| *a = x.min(*a));
+                }
+                1 => {
+                    st.chmax(l..r, x);
+                    true_a[l..r].iter_mut().for_each(|a| *a = x.max(*a));
+                }
+                _ => {
+                    st.add(l..r, x);
+                    true_a[l..r].iter_mut().for_each(|a| *a += x);
+                }
+            }
+
+            let l = rng.gen_range(0..=n);
+            let r = rng.gen_range(l..=n);
+            let range = &true_a[l..r];
+            assert_eq!(st.sum(l..r), range.iter().sum::<i64>());
+            assert_eq!(
+                st.max(l..r),
+                range.iter().copied().max().unwrap_or(i64::MIN)
+            );
+            assert_eq!(
+                st.min(l..r),
+                range.iter().copied().min().unwrap_or(i64::MAX)
+            );
+        }
+        for (i, &a) in true_a.iter().enumerate() {
+            assert_eq!(st.get(i), a);
+        }
+    }
+
+    let mut st = BeatsSegmentTree::<u32>::new(8);
+    st.add(.., 10);
+    st.chmin(2..6, 4);
+    st.chmax(4.., 7);
+    assert_eq!(st.sum(..), 10 + 10 + 4 + 4 + 7 + 7 + 10 + 10);
+    assert_eq!(st.min(..), 4);
+    assert_eq!(st.max(2..6), 7);
+
+    println!("BeatsSegmentTree VALIDATED");
+    println!();
+}
+
+#[allow(dead_code)]
+fn validate_wavelet_matrix_dstruct() {
+    let mut rng = SmallRng::from_entropy();
+
+    println!("[Validate WaveletMatrix]");
+    for _ in 0..64 {
+        let n = rng.gen_range(0..256);
+        let sigma = rng.gen_range(1..=64);
+        let a: Vec<i32> = (0..n).map(|_| rng.gen_range(-sigma..sigma)).collect();
+        let wm: WaveletMatrix<_> = a.iter().copied().collect();
+        assert_eq!(wm.len(), n);
+
+        for (i, x) in a.iter().enumerate() {
+            assert_eq!(wm.get(i), x);
+        }
+
+        for _ in 0..64 {
+            let x = rng.gen_range(-sigma - 1..=sigma);
+            let i = rng.gen_range(0..=n);
+            assert_eq!(wm.rank(&x, i), a[..i].iter().filter(|&&y| y == x).count());
+
+            let k = rng.gen_range(0..4);
+            let true_pos = a.iter().enumerate().filter(|&(_, &y)| y == x).nth(k);
+            assert_eq!(wm.select(&x, k), true_pos.map(|(i, _)| i));
+
+            let l = rng.gen_range(0..=n);
+            let r = rng.gen_range(l..=n);
+            let mut sorted = a[l..r].to_vec();
+            sorted.sort_unstable();
+            let k = rng.gen_range(0..=r - l);
+            assert_eq!(wm.quantile(l..r, k), sorted.get(k));
+
+            let lo = rng.gen_range(-sigma - 1..=sigma);
+            let hi = rng.gen_range(-sigma - 1..=sigma);
+            let count = |f: &dyn Fn(i32) -> bool| a[l..r].iter().filter(|&&y| f(y)).count();
+            assert_eq!(wm.range_freq(l..r, ..x), count(&|y| y < x));
+            assert_eq!(wm.range_freq(l..r, lo..hi), count(&|y| lo <= y && y < hi));
+            assert_eq!(wm.range_freq(l..r, lo..=hi), count(&|y| lo <= y && y <= hi));
+        }
+    }
+    println!("WaveletMatrix VALIDATED");
+    println!();
+}
+
+#[allow(dead_code)]
+fn validate_range_fenwick_tree_dstruct() {
+    use groups::{
+        Associativity, Commutativity, Identity, Invertibillity, Magma, ScalarMultiplication,
+    };
+
+    /// Addition modulo 7, which uses the default `mul_scalar`.
+    #[derive(Clone)]
+    struct AddMod7;
+    impl Magma for AddMod7 {
+        type Elem = u8;
+        fn add(&self, lhs: u8, rhs: u8) -> u8 {
+            (lhs + rhs) % 7
+        }
+    }
+    impl Identity for AddMod7 {
+        fn id(&self) -> u8 {
+            0
+        }
+    }
+    impl Invertibillity for AddMod7 {
+        fn inv(&self, x: u8) -> u8 {
+            (7 - x) % 7
+        }
+    }
+    impl Associativity for AddMod7 {}
+    impl Commutativity for AddMod7 {}
+    impl ScalarMultiplication for AddMod7 {}
+
+    let mut rng = SmallRng::from_entropy();
+
+    println!("[Validate RangeFenwickTree]");
+    for (i, x) in (0..7).enumerate() {
+        assert_eq!(
+            AddMod7.mul_scalar(x, 1000 + i),
+            (x as usize * (1000 + i) % 7) as u8
+        );
+    }
+    // `300` doesn't fit `u8`, so these fall back to double-and-add.
+    let wrapping = groups::NumAdditiveGroups::<std::num::Wrapping<u8>>::new();
+    assert_eq!(
+        wrapping.mul_scalar(std::num::Wrapping(1), 300),
+        std::num::Wrapping(44)
+    );
+    assert_eq!(groups::NumAdditiveGroups::<u8>::new().mul_scalar(0, 300), 0);
+    assert_eq!(
+        groups::NumAdditiveGroups::<i8>::new().mul_scalar(1, 127),
+        127
+    );
+    for _ in 0..64 {
+        let n = rng.gen_range(0..128);
+        let mut ft = RangeFenwickTree::new(groups::NumAdditiveGroups::<i64>::new(), n);
+        let mut ft_mod = RangeFenwickTree::new(AddMod7, n);
+        assert!(format!("{:?}", ft).starts_with("RangeFenwickTree"));
+        let mut true_a = vec![0i64; n];
+
+        for _ in 0..256 {
+            let l = rng.gen_range(0..=n);
+            let r = rng.gen_range(l..=n);
+            let dx = rng.gen_range(-1000..1000);
+            ft.range_add(l..r, dx);
+            ft_mod.range_add(l..r, dx.rem_euclid(7) as u8);
+            true_a[l..r].iter_mut().for_each(|a| *a += dx);
+
+            let l = rng.gen_range(0..=n);
+            let r = rng.gen_range(l..=n);
+            let true_sum = true_a[l..r].iter().sum::<i64>();
+            assert_eq!(ft.range_sum(l..r), true_sum);
+            assert_eq!(ft_mod.range_sum(l..r), true_sum.rem_euclid(7) as u8);
+        }
+        for (i, &a) in true_a.iter().enumerate() {
+            assert_eq!(ft.get(i), a);
+        }
+    }
+    println!("RangeFenwickTree VALIDATED");
+    println!();
+}
+
+#[allow(dead_code)]
+fn validate_fenwick_tree_lower_bound_dstruct() {
+    let mut rng = SmallRng::from_entropy();
+
+    println!("[Validate FenwickTree::lower_bound]");
+    for _ in 0..64 {
+        let n = rng.gen_range(0..256);
+        let mut a: Vec<u32> = (0..n).map(|_| rng.gen_range(0..8)).collect();
+        let mut ft = FenwickTree::new(groups::NumAdditiveGroups::<u32>::new());
+        ft.extend(a.iter().copied());
+
+        for _ in 0..64 {
+            if n != 0 {
+                let i = rng.gen_range(0..n);
+                let dx = rng.gen_range(0..8);
+                ft.update_add(i, dx);
+                a[i] += dx;
+            }
+
+            let total: u32 = a.iter().sum();
+            let target = rng.gen_range(0..total + 2);
+            let mut true_i = 0;
+            let mut ps = 0;
+            while true_i < n && ps < target {
+                ps += a[true_i];
+                true_i += 1;
+            }
+            let true_i = if target <= ps { Some(true_i) } else { None };
+            assert_eq!(ft.lower_bound(&target), true_i);
+        }
+    }
+    println!("FenwickTree::lower_bound VALIDATED");
+    println!();
+}
+
+#[allow(dead_code)]
+fn validate_fenwick_tree_build_dstruct() {
+    let mut rng = SmallRng::from_entropy();
+
+    println!("[Validate FenwickTree::from_vec]");
+    for _ in 0..64 {
+        let n = rng.gen_range(0..256);
+        let mut a: Vec<i64> = (0..n).map(|_| rng.gen_range(-1000..1000)).collect();
+        let mut ft = FenwickTree::from_vec(groups::NumAdditiveGroups::new(), a.clone());
+
+        let mut pushed = FenwickTree::new(groups::NumAdditiveGroups::new());
+        for &x in &a {
+            pushed.push(x);
+        }
+        for i in 0..=n {
+            assert_eq!(ft.prefix_sum(i), pushed.prefix_sum(i));
+        }
+
+        let xs: Vec<i64> = (0..rng.gen_range(0..64))
+            .map(|_| rng.gen_range(-1000..1000))
+            .collect();
+        ft.extend(xs.iter().copied());
+        a.extend(xs);
+        assert_eq!(ft.to_vec(), a);
+
+        for _ in 0..64 {
+            let l = rng.gen_range(0..=a.len());
+            let r = rng.gen_range(l..=a.len());
+            assert_eq!(ft.range_sum(l..r), a[l..r].iter().sum::<i64>());
+            assert_eq!(ft.range_sum(l..), a[l..].iter().sum::<i64>());
+        }
+        assert_eq!(ft.into_values(), a);
+    }
+    println!("FenwickTree::from_vec VALIDATED");
+    println!();
+}
+
+#[allow(dead_code)]
+fn validate_compressed_fenwick_tree_dstruct() {
+    use std::collections::BTreeMap;
+
+    let mut rng = SmallRng::from_entropy();
+
+    println!("[Validate CompressedFenwickTree]");
+    for _ in 0..16 {
+        let keys: Vec<u64> = (0..rng.gen_range(0..256)).map(|_| rng.gen()).collect();
+        let mut ft = CompressedFenwickTree::new(
+            groups::NumAdditiveGroups::<i64>::new(),
+            keys.iter().copied(),
+        );
+        let mut true_map: BTreeMap<u64, i64> = keys.iter().map(|&k| (k, 0)).collect();
+        assert_eq!(ft.len(), true_map.len());
+        assert!(format!("{:?}", ft).starts_with("CompressedFenwickTree"));
+
+        for _ in 0..256 {
+            if let Some(&key) = keys.choose(&mut rng) {
+                let dx = rng.gen_range(-1000..1000);
+                if rng.gen() {
+                    ft.update_add(&key, dx);
+                    *true_map.get_mut(&key).unwrap() += dx;
+                } else {
+                    ft.update_set(&key, dx);
+                    true_map.insert(key, dx);
+                }
+                assert_eq!(ft.get(&key), true_map[&key]);
+            }
+
+            let key = match keys.choose(&mut rng) {
+                Some(&key) if rng.gen() => key,
+                _ => rng.gen(),
+            };
+            assert_eq!(
+                ft.prefix_sum(&key),
+                true_map.range(..key).map(|(_, x)| x).sum::<i64>()
+            );
+
+            let (mut lo, mut hi): (u64, u64) = (rng.gen(), rng.gen());
+            if hi < lo {
+                std::mem::swap(&mut lo, &mut hi);
+            }
+            assert_eq!(
+                ft.range_sum(lo..hi),
+                true_map.range(lo..hi).map(|(_, x)| x).sum::<i64>()
+            );
+            assert_eq!(
+                ft.range_sum(lo..=hi),
+                true_map.range(lo..=hi).map(|(_, x)| x).sum::<i64>()
+            );
+        }
+    }
+    println!("CompressedFenwickTree VALIDATED");
+    println!();
+}
+
+#[allow(dead_code)]
+fn validate_indexed_multiset_dstruct() {
+    let mut rng = SmallRng::from_entropy();
+
+    println!("[Validate IndexedMultiset]");
+    for _ in 0..16 {
+        let universe = rng.gen_range(1..256);
+        let mut set = IndexedMultiset::new(universe);
+        let mut true_set: Vec<usize> = Vec::new();
+
+        for _ in 0..512 {
+            let x = rng.gen_range(0..universe);
+            if rng.gen_range(0..3) != 0 {
+                set.insert(x);
+                let i = true_set.partition_point(|&y| y <= x);
+                true_set.insert(i, x);
+            } else {
+                let removed = match true_set.binary_search(&x) {
+                    Ok(i) => {
+                        true_set.remove(i);
+                        true
+                    }
+                    Err(_) => false,
+                };
+                assert_eq!(set.remove(x), removed);
+            }
+            assert_eq!(set.len(), true_set.len());
+
+            let x = rng.gen_range(0..universe + 2);
+            assert_eq!(set.count(x), true_set.iter().filter(|&&y| y == x).count());
+            assert_eq!(set.rank(x), true_set.partition_point(|&y| y < x));
+            let k = rng.gen_range(0..true_set.len() + 2);
+            assert_eq!(set.kth(k), true_set.get(k).copied());
+        }
+        assert!(set.iter().eq(true_set.iter().copied()));
+        assert!(set.iter().rev().eq(true_set.iter().rev().copied()));
+    }
+    println!("IndexedMultiset VALIDATED");
+    println!();
+}
+
+#[allow(dead_code)]
+fn validate_groups_dstruct() {
+    use groups::*;
+
+    /// Checks `SegmentTree::sum` over all the ranges against folding the elements.
+    fn check<G: Monoid + Clone>(group: G, a: &[G::Elem])
+    where
+        G::Elem: PartialEq + std::fmt::Debug,
+    {
+        let st = SegmentTree::from_iter_in(group.clone(), a.iter().cloned());
+        for l in 0..=a.len() {
+            let mut true_sum = group.id();
+            for r in l..=a.len() {
+                assert_eq!(st.sum(l, r), true_sum);
+                if r < a.len() {
+                    true_sum = group.add(true_sum, a[r].clone());
+                }
+            }
+        }
+    }
+
+    let mut rng = SmallRng::from_entropy();
+
+    println!("[Validate groups]");
+    for _ in 0..16 {
+        let n = rng.gen_range(1..32);
+        let a: Vec<i64> = (0..n).map(|_| rng.gen_range(-64..64)).collect();
+        let small: Vec<i64> = (0..n).map(|_| rng.gen_range(1..4)).collect();
+
+        check(NumMultiplicative::<i64>::new(), &small);
+        check(
+            NumMultiplicative::<f64>::new(),
+            &small.iter().map(|&x| x as f64).collect::<Vec<_>>(),
+        );
+        check(Min::<i64>::new(), &a);
+        check(Max::<i64>::new(), &a);
+        check(BitXor::<i64>::new(), &a);
+        check(BitAnd::<i64>::new(), &a);
+        check(BitOr::<i64>::new(), &a);
+        check(
+            Gcd::<i64>::new(),
+            &a.iter().map(|x| x.abs()).collect::<Vec<_>>(),
+        );
+        check(Lcm::<i64>::new(), &small);
+        check(
+            First::<i64>::new(),
+            &a.iter()
+                .map(|&x| Some(x).filter(|x| x % 3 == 0))
+                .collect::<Vec<_>>(),
+        );
+        check(
+            Last::<i64>::new(),
+            &a.iter()
+                .map(|&x| Some(x).filter(|x| x % 3 == 0))
+                .collect::<Vec<_>>(),
+        );
+        check(
+            Concat::<String>::new(),
+            &a.iter().map(|x| x.to_string()).collect::<Vec<_>>(),
+        );
+        check(
+            Concat::<Vec<i64>>::new(),
+            &a.iter()
+                .map(|&x| vec![x; (x & 3) as usize])
+                .collect::<Vec<_>>(),
+        );
+
+        let indexed: Vec<_> = small
+            .iter()
+            .enumerate()
+            .map(|(i, &x)| Some((x, i)))
+            .collect();
+        check(ArgMin::<i64>::new(), &indexed);
+        check(ArgMax::<i64>::new(), &indexed);
+        let max = *small.iter().max().unwrap();
+        let first_max = small.iter().position(|&x| x == max).unwrap();
+        assert_eq!(
+            SegmentTree::from_iter_in(ArgMax::new(), indexed).sum(0, n),
+            Some((max, first_max))
+        );
+
+        let mut ft = FenwickTree::new(BitXor::<i64>::new());
+        ft.extend(a.iter().copied());
+        for (i, &x) in a.iter().enumerate() {
+            assert_eq!(ft.get(i), x);
+        }
+    }
+    assert_eq!(Gcd::<i64>::new().add(-12, 18), 6);
+
+    println!("groups VALIDATED");
+    println!();
+}
+
+#[allow(dead_code)]
+fn validate_group_products_dstruct() {
+    use groups::*;
+
+    /// A newtype around the amount of cents.
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    struct Cents(i64);
+    impl From<i64> for Cents {
+        fn from(x: i64) -> Self {
+            Self(x)
+        }
+    }
+    impl From<Cents> for i64 {
+        fn from(x: Cents) -> Self {
+            x.0
+        }
+    }
+
+    let mut rng = SmallRng::from_entropy();
+
+    println!("[Validate group products]");
+    for _ in 0..16 {
+        let n = rng.gen_range(1..64);
+        let a: Vec<i64> = (0..n).map(|_| rng.gen_range(-1000..1000)).collect();
+
+        let stats = (
+            NumAdditiveGroups::<i64>::new(),
+            NumAdditiveGroups::<i64>::new(),
+            Min::<i64>::new(),
+            Max::<i64>::new(),
+        );
+        let st = SegmentTree::from_iter_in(stats, a.iter().map(|&x| (x, 1, x, x)));
+
+        let mut ft = FenwickTree::new(Product::new(
+            NumAdditiveGroups::<i64>::new(),
+            BitXor::<i64>::new(),
+        ));
+        ft.extend(a.iter().map(|&x| (x, x)));
+
+        let mut rft = RangeFenwickTree::new(
+            (
+                NumAdditiveGroups::<i64>::new(),
+                Mapped::<_, Cents>::new(NumAdditiveGroups::<i64>::new()),
+            ),
+            n,
+        );
+
+        for _ in 0..64 {
+            let l = rng.gen_range(0..=n);
+            let r = rng.gen_range(l..=n);
+            let range = &a[l..r];
+
+            let sum = range.iter().sum::<i64>();
+            let min = range.iter().copied().min().unwrap_or(i64::MAX);
+            let max = range.iter().copied().max().unwrap_or(i64::MIN);
+            assert_eq!(st.sum(l, r), (sum, (r - l) as i64, min, max));
+
+            let xor = range.iter().fold(0, |acc, &x| acc ^ x);
+            assert_eq!(ft.range_sum(l..r), (sum, xor));
+
+            let dx = rng.gen_range(-1000..1000);
+            rft.range_add(l..r, (dx, Cents(2 * dx)));
+            let i = rng.gen_range(0..n);
+            let (x, cents) = rft.get(i);
+            assert_eq!(cents, Cents(2 * x));
+        }
+        for (i, &x) in a.iter().enumerate() {
+            assert_eq!(ft.get(i), (x, x));
+        }
+    }
+    println!("group products VALIDATED");
+    println!();
+}
+
+#[allow(dead_code)]
+fn validate_fn_groups_dstruct() {
+    use groups::{FnGroup, FnMonoid};
+
+    const P: u64 = 998_244_353;
+
+    fn max(a: i64, b: i64) -> i64 {
+        a.max(b)
+    }
+
+    let mut rng = SmallRng::from_entropy();
+
+    println!("[Validate FnMonoid and FnGroup]");
+    for _ in 0..16 {
+        let n = rng.gen_range(1..64);
+        let maps: Vec<(u64, u64)> = (0..n)
+            .map(|_| (rng.gen_range(0..P), rng.gen_range(0..P)))
+            .collect();
+        let a: Vec<i64> = (0..n).map(|_| rng.gen_range(-1000..1000)).collect();
+
+        // Composition of affine maps, which isn't commutative.
+        let affine = FnMonoid::new((1, 0), |(a1, b1): (u64, u64), (a2, b2): (u64, u64)| {
+            (a2 * a1 % P, (a2 * b1 + b2) % P)
+        });
+        let st = SegmentTree::from_iter_in(affine, maps.iter().copied());
+        let st_max = SegmentTree::from_iter_in(FnMonoid::new(i64::MIN, max), a.iter().copied());
+
+        let sum = FnGroup::new(0, |a: i64, b: i64| a + b, |a: i64| -a).assume_commutative();
+        let mut ft = FenwickTree::new(sum);
+        ft.extend(a.iter().copied());
+
+        for _ in 0..64 {
+            let l = rng.gen_range(0..=n);
+            let r = rng.gen_range(l..=n);
+            let true_map = maps[l..r].iter().fold((1, 0), |(a1, b1), &(a2, b2)| {
+                (a2 * a1 % P, (a2 * b1 + b2) % P)
+            });
+            assert_eq!(st.sum(l, r), true_map);
+            assert_eq!(
+                st_max.sum(l, r),
+                a[l..r].iter().copied().max().unwrap_or(i64::MIN)
+            );
+            assert_eq!(ft.range_sum(l..r), a[l..r].iter().sum::<i64>());
+        }
+        for (i, &x) in a.iter().enumerate() {
+            assert_eq!(ft.get(i), x);
+        }
+    }
+    println!("FnMonoid and FnGroup VALIDATED");
+    println!();
+}
+
+#[allow(dead_code)]
+fn validate_group_laws_dstruct() {
+    use groups::{laws::*, *};
+
+    /// Subtraction, which is wrongly marked as associative and commutative.
+    struct WrongSub;
+    impl Magma for WrongSub {
+        type Elem = i64;
+        fn add(&self, lhs: i64, rhs: i64) -> i64 {
+            lhs - rhs
+        }
+    }
+    impl Identity for WrongSub {
+        fn id(&self) -> i64 {
+            0
+        }
+    }
+    impl Associativity for WrongSub {}
+    impl Commutativity for WrongSub {}
+
+    let mut rng = SmallRng::from_entropy();
+    let small = |rng: &mut SmallRng| rng.gen_range(-1000..1000i64);
+
+    println!("[Validate groups::laws]");
+    check_abelian_group(&NumAdditiveGroups::<i64>::new(), &mut rng, small, 256).unwrap();
+    check_abelian_group(&BitXor::<u32>::new(), &mut rng, |rng| rng.gen(), 256).unwrap();
+    check_commutative_monoid(&Min::<i64>::new(), &mut rng, small, 256).unwrap();
+    check_commutative_monoid(
+        &Gcd::<i64>::new(),
+        &mut rng,
+        |rng| rng.gen_range(0..1000),
+        256,
+    )
+    .unwrap();
+    assert!(check_identity(
+        &Gcd::<i64>::new(),
+        &mut rng,
+        |rng| rng.gen_range(-1000..0),
+        256
+    )
+    .is_err());
+    check_monoid(
+        &Concat::<String>::new(),
+        &mut rng,
+        |rng| small(rng).to_string(),
+        256,
+    )
+    .unwrap();
+    check_monoid(
+        &Last::<i64>::new(),
+        &mut rng,
+        |rng| Some(small(rng)).filter(|x| x % 2 == 0),
+        256,
+    )
+    .unwrap();
+    assert!(check_commutativity(
+        &Concat::<String>::new(),
+        &mut rng,
+        |rng| small(rng).to_string(),
+        256
+    )
+    .is_err());
+
+    let err = check_commutative_monoid(&WrongSub, &mut rng, small, 256).unwrap_err();
+    assert_eq!(err.law, Law::Associativity);
+    let [a, b, c] = [err.elements[0], err.elements[1], err.elements[2]];
+    assert_ne!((a - b) - c, a - (b - c));
+    assert!(err
+        .to_string()
+        .starts_with("Associativity doesn't hold for"));
+
+    let err = check_identity(&WrongSub, &mut rng, |rng| rng.gen_range(1..1000), 256).unwrap_err();
+    assert_eq!(err.law, Law::Identity);
+
+    // A wrong inverse, which only works for zero.
+    let wrong_inv = FnGroup::new(0, |a: i64, b: i64| a + b, |a: i64| a);
+    let err = check_group(&wrong_inv, &mut rng, small, 256).unwrap_err();
+    assert_eq!(err.law, Law::Inverse);
+
+    println!("groups::laws VALIDATED");
+    println!();
+}
+
+#[allow(dead_code)]
+fn validate_mod_int_dstruct() {
+    use groups::{laws::*, *};
+
+    const P: u64 = 998_244_353;
+    const BIG: u64 = (1 << 62) - 57;
+    type Mint = ModInt<P>;
+    type Big = ModInt<BIG>;
+
+    let mut rng = SmallRng::from_entropy();
+
+    println!("[Validate ModInt]");
+    for _ in 0..1000 {
+        let (a, b) = (rng.gen_range(0..P), rng.gen_range(1..P));
+        let (x, y) = (Mint::new(a), Mint::new(b));
+        assert_eq!(x.value(), a);
+        assert_eq!((x + y).value(), (a + b) % P);
+        assert_eq!((x - y).value(), (a + P - b) % P);
+        assert_eq!((x * y).value(), a * b % P);
+        assert_eq!((x / y * y).value(), a);
+        assert_eq!((y * y.inv()).value(), 1);
+        assert_eq!((-x + x).value(), 0);
+        assert_eq!(x % y, Mint::new(0));
+
+        let (a, b) = (rng.gen_range(0..BIG), rng.gen_range(1..BIG));
+        let (x, y) = (Big::new(a), Big::new(b));
+        assert_eq!((x * y).value() as u128, a as u128 * b as u128 % BIG as u128);
+        assert_eq!((x / y * y).value(), a);
+
+        let n = rng.gen_range(0..100u64);
+        let pow = (0..n).fold(1, |acc, _| acc * a as u128 % BIG as u128);
+        assert_eq!(x.pow(n).value() as u128, pow);
+    }
+    assert_eq!(Mint::from(-1i64).value(), P - 1);
+    assert_eq!(Mint::from(P + 5).value(), 5);
+    assert_eq!(Mint::new(3).pow(P - 1).value(), 1);
+    assert_eq!(ModInt::<15>::new(6).try_inv(), None);
+    assert_eq!(ModInt::<15>::new(7).inv().value(), 13);
+    assert_eq!(num::Num::from_str_radix("-10", 10), Ok(Mint::new(P - 10)));
+    assert_eq!(
+        (1..=10u64).map(Mint::new).product::<Mint>().value(),
+        3_628_800
+    );
+    assert_eq!(format!("{:?}", Mint::from(-2i32)), format!("{}", P - 2));
+
+    // Runtime moduli.
+    for _ in 0..100 {
+        let m = rng.gen_range(0..1u64 << 40) | 1;
+        DynModulus::set(m);
+        let (a, b) = (rng.gen_range(0..m), rng.gen_range(0..m));
+        let (x, y) = (DynModInt::new(a), DynModInt::<DynModulus>::new(b));
+        assert_eq!(DynModInt::<DynModulus>::modulus(), m);
+        assert_eq!((x * y).value() as u128, a as u128 * b as u128 % m as u128);
+        assert_eq!((x + y).value(), (a + b) % m);
+        if let Some(inv) = y.try_inv() {
+            assert_eq!((y * inv).value(), 1 % m);
+        }
+    }
+
+    // As the elements of the other structures.
+    let gen = |rng: &mut SmallRng| Mint::new(rng.gen());
+    check_abelian_group(&NumAdditiveGroups::<Mint>::new(), &mut rng, gen, 256).unwrap();
+    check_abelian_group(
+        &NumMultiplicative::<Mint>::new(),
+        &mut rng,
+        |rng| Mint::new(rng.gen_range(1..P)),
+        256,
+    )
+    .unwrap();
+
+    let n = 100;
+    let a: Vec<Mint> = (0..n).map(|_| gen(&mut rng)).collect();
+    let fenwick = FenwickTree::from_vec(NumAdditiveGroups::new(), a.clone());
+    let segment = SegmentTree::from_iter_in(NumMultiplicative::new(), a.iter().copied());
+    for _ in 0..100 {
+        let l = rng.gen_range(0..n);
+        let r = rng.gen_range(l..=n);
+        assert_eq!(fenwick.range_sum(l..r), a[l..r].iter().copied().sum());
+        assert_eq!(segment.sum(l, r), a[l..r].iter().copied().product());
+        assert_eq!(fenwick.get(l.min(n - 1)), a[l.min(n - 1)]);
+    }
+
+    let x = gen(&mut rng);
+    let naive = a.iter().rev().fold(Mint::new(0), |acc, &c| acc * x + c);
+    assert_eq!(eval_poly(&a, x), naive);
+
+    println!("ModInt VALIDATED");
+    println!();
+}
+
+#[allow(dead_code)]
+fn validate_semirings_dstruct() {
+    use groups::{laws::*, *};
+
+    type Matrix<T> = Vec<Vec<T>>;
+
+    fn mat_mul<S: Semiring>(s: &S, a: &Matrix<S::Elem>, b: &Matrix<S::Elem>) -> Matrix<S::Elem> {
+        let n = a.len();
+        (0..n)
+            .map(|i| {
+                (0..n)
+                    .map(|j| {
+                        (0..n).fold(s.zero(), |acc, k| {
+                            s.add(acc, s.mul(a[i][k].clone(), b[k][j].clone()))
+                        })
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn mat_pow<S: Semiring>(s: &S, mut a: Matrix<S::Elem>, mut k: usize) -> Matrix<S::Elem> {
+        let n = a.len();
+        let mut acc: Matrix<S::Elem> = (0..n)
+            .map(|i| {
+                (0..n)
+                    .map(|j| if i == j { s.one() } else { s.zero() })
+                    .collect()
+            })
+            .collect();
+        while k != 0 {
+            if k & 1 != 0 {
+                acc = mat_mul(s, &acc, &a);
+            }
+            a = mat_mul(s, &a, &a);
+            k >>= 1;
+        }
+        acc
+    }
+
+    /// The `k`th row of `a^k`, one step at a time.
+    fn walks<S: Semiring>(s: &S, a: &Matrix<S::Elem>, from: usize, k: usize) -> Vec<S::Elem> {
+        let n = a.len();
+        let mut row: Vec<_> = (0..n)
+            .map(|i| if i == from { s.one() } else { s.zero() })
+            .collect();
+        for _ in 0..k {
+            row = (0..n)
+                .map(|j| {
+                    (0..n).fold(s.zero(), |acc, i| {
+                        s.add(acc, s.mul(row[i].clone(), a[i][j].clone()))
+                    })
+                })
+                .collect();
+        }
+        row
+    }
+
+    type Mint = ModInt<998_244_353>;
+
+    let mut rng = SmallRng::from_entropy();
+    let small = |rng: &mut SmallRng| rng.gen_range(-1000..1000i64);
+    let tropical = |rng: &mut SmallRng| match rng.gen_range(0..8) {
+        0 => i64::MAX,
+        1 => i64::MIN,
+        _ => rng.gen_range(-1000..1000),
+    };
+
+    println!("[Validate groups::Semiring]");
+    check_ring(
+        &NumRing::<i64>::new(NumAdditiveGroups::new(), NumMultiplicative::new()),
+        &mut rng,
+        small,
+        256,
+    )
+    .unwrap();
+    check_semiring(
+        &NumRing::<u64>::default(),
+        &mut rng,
+        |rng| rng.gen_range(0..1000),
+        256,
+    )
+    .unwrap();
+    check_ring(
+        &NumRing::<Mint>::default(),
+        &mut rng,
+        |rng| Mint::new(rng.gen()),
+        256,
+    )
+    .unwrap();
+    check_semiring(
+        &MinPlus::<i64>::default(),
+        &mut rng,
+        |rng| tropical(rng).max(-1000),
+        256,
+    )
+    .unwrap();
+    check_semiring(
+        &MaxPlus::<i64>::default(),
+        &mut rng,
+        |rng| tropical(rng).min(1000),
+        256,
+    )
+    .unwrap();
+    // Without the absorbing infinity, the annihilation fails.
+    let plain_min_plus = AddMul::new(Min::<i64>::new(), NumAdditiveGroups::<i64>::new());
+    assert_eq!(
+        check_annihilation(
+            &plain_min_plus,
+            &mut rng,
+            |rng| rng.gen_range(-1000..0),
+            256
+        )
+        .unwrap_err()
+        .law,
+        Law::Annihilation
+    );
+
+    let field = NumRing::<Mint>::default();
+    check_field(&field, &mut rng, |rng| Mint::new(rng.gen_range(0..4)), 256).unwrap();
+    check_field(&field, &mut rng, |rng| Mint::new(rng.gen()), 256).unwrap();
+    DynModulus::set(1_000_000_007);
+    check_field(
+        &NumRing::<DynModInt>::default(),
+        &mut rng,
+        |rng| DynModInt::new(rng.gen()),
+        256,
+    )
+    .unwrap();
+    for _ in 0..100 {
+        let x = Mint::new(rng.gen_range(1..998_244_353));
+        assert_eq!(field.div(field.one(), x), x.inv());
+        assert_eq!(field.sub(field.zero(), x), field.neg(x));
+    }
+
+    // Walks of length `k` through the powers of the adjacency matrix.
+    let n = 6;
+    let edges: Matrix<bool> = (0..n)
+        .map(|_| (0..n).map(|_| rng.gen_bool(0.5)).collect())
+        .collect();
+    let weights: Matrix<i64> = (0..n)
+        .map(|_| (0..n).map(|_| rng.gen_range(-10..100)).collect())
+        .collect();
+    for k in 0..20 {
+        let count = NumRing::<Mint>::default();
+        let a: Matrix<Mint> = edges
+            .iter()
+            .map(|row| row.iter().map(|&e| Mint::from(e as u64)).collect())
+            .collect();
+        assert_eq!(mat_pow(&count, a.clone(), k)[0], walks(&count, &a, 0, k));
+
+        let shortest = MinPlus::<i64>::default();
+        let a: Matrix<i64> = (0..n)
+            .map(|i| {
+                (0..n)
+                    .map(|j| if edges[i][j] { weights[i][j] } else { i64::MAX })
+                    .collect()
+            })
+            .collect();
+        let pow = mat_pow(&shortest, a.clone(), k);
+        assert_eq!(pow[1], walks(&shortest, &a, 1, k));
+
+        let longest = MaxPlus::<i64>::default();
+        let a: Matrix<i64> = (0..n)
+            .map(|i| {
+                (0..n)
+                    .map(|j| if edges[i][j] { weights[i][j] } else { i64::MIN })
+                    .collect()
+            })
+            .collect();
+        let pow = mat_pow(&longest, a.clone(), k);
+        assert_eq!(pow[2], walks(&longest, &a, 2, k));
+        if k == 1 {
+            assert_eq!(pow, a);
+        }
+    }
+
+    println!("groups::Semiring VALIDATED");
+    println!();
+}
+
+#[allow(dead_code)]
+fn validate_lazy_segment_tree_dstruct() {
+    use groups::{Associativity, Identity, Magma, MonoidAction};
+
+    /// Range minimum.
+    struct MinI64;
+    impl Magma for MinI64 {
+        type Elem = i64;
+        fn add(&self, lhs: i64, rhs: i64) -> i64 {
+            lhs.min(rhs)
+        }
+    }
+    impl Identity for MinI64 {
+        fn id(&self) -> i64 {
+            i64::MAX
+        }
+    }
+    impl Associativity for MinI64 {}
+
+    /// Range addition, acting on `MinI64`.
+    struct AddI64;
+    impl Magma for AddI64 {
+        type Elem = i64;
+        fn add(&self, lhs: i64, rhs: i64) -> i64 {
+            lhs + rhs
+        }
+    }
+    impl Identity for AddI64 {
+        fn id(&self) -> i64 {
+            0
+        }
+    }
+    impl Associativity for AddI64 {}
+    impl MonoidAction<MinI64> for AddI64 {
+        fn act(&self, action: i64, x: i64) -> i64 {
+            if x == i64::MAX {
+                x
+            } else {
+                x + action
+            }
+        }
+    }
+
+    /// Range sum, as `(sum, len)` so that assignment knows the length of the segment.
+    struct SumLen;
+    impl Magma for SumLen {
+        type Elem = (i64, i64);
+        fn add(&self, lhs: (i64, i64), rhs: (i64, i64)) -> (i64, i64) {
+            (lhs.0 + rhs.0, lhs.1 + rhs.1)
+        }
+    }
+    impl Identity for SumLen {
+        fn id(&self) -> (i64, i64) {
+            (0, 0)
+        }
+    }
+    impl Associativity for SumLen {}
+
+    /// Range assignment, acting on `SumLen`. The newer assignment is on the left.
+    struct Assign;
+    impl Magma for Assign {
+        type Elem = Option<i64>;
+        fn add(&self, lhs: Option<i64>, rhs: Option<i64>) -> Option<i64> {
+            lhs.or(rhs)
+        }
+    }
+    impl Identity for Assign {
+        fn id(&self) -> Option<i64> {
+            None
+        }
+    }
+    impl Associativity for Assign {}
+    impl MonoidAction<SumLen> for Assign {
+        fn act(&self, action: Option<i64>, x: (i64, i64)) -> (i64, i64) {
+            match action {
+                Some(v) => (v * x.1, x.1),
+                None => x,
+            }
+        }
+    }
+
+    let mut rng = SmallRng::from_entropy();
+
+    const N: usize = 1000;
+    const K: usize = 10 * 1000;
+
+    println!("[Validate LazySegmentTree]");
+
+    let mut true_a: Vec<i64> = (0..N).map(|_| rng.gen_range(-1000..1000)).collect();
+    let mut st = LazySegmentTree::new(MinI64, AddI64, N);
+    st.build(true_a.iter().cloned());
+    for _ in 0..K {
+        let l = rng.gen_range(0..=N);
+        let r = rng.gen_range(l..=N);
+        match rng.gen_range(0..3) {
+            0 => {
+                let dx = rng.gen_range(-1000..1000);
+                st.apply(l..r, dx);
+                true_a[l..r].iter_mut().for_each(|x| *x += dx);
+            }
+            1 if l < N => {
+                let x = rng.gen_range(-1000..1000);
+                st.update(l, x);
+                true_a[l] = x;
+            }
+            _ => {}
+        }
+        assert_eq!(
+            st.sum(l..r),
+            true_a[l..r].iter().cloned().min().unwrap_or(i64::MAX)
+        );
+    }
+    assert!((0..N).all(|i| st.get(i) == true_a[i]));
+
+    let mut true_a: Vec<i64> = (0..N).map(|_| rng.gen_range(-1000..1000)).collect();
+    let mut st = LazySegmentTree::new(SumLen, Assign, N);
+    st.build(true_a.iter().map(|&x| (x, 1)));
+    for _ in 0..K {
+        let l = rng.gen_range(0..=N);
+        let r = rng.gen_range(l..=N);
+        if rng.gen() {
+            let x = rng.gen_range(-1000..1000);
+            st.apply(l..r, Some(x));
+            true_a[l..r].iter_mut().for_each(|y| *y = x);
+        }
+        assert_eq!(st.sum(l..r), (true_a[l..r].iter().sum(), (r - l) as i64));
+    }
+    assert_eq!(st.sum(..), (true_a.iter().sum(), N as i64));
+
+    println!("LazySegmentTree VALIDATED");
+    println!();
+}
+
 #[allow(dead_code)]
 fn validate_btree_dstruct() {
     let mut rng = SmallRng::from_entropy();
@@ -229,82 +1661,293 @@ fn validate_btree_dstruct() {
     println!();
 }
 
-// #[allow(dead_code)]
-// fn validate_ord_btree_dstruct() {
-//     let mut rng = SmallRng::from_entropy();
-//     let mut map = OrdBTree::new();
-//
-//     const K: usize = 64; // 16;
-//     const N: usize = K * 1024;
-//     // const K: usize = 4;
-//     // const N: usize = K * 8;
-//
-//     let mut true_map = Vec::with_capacity(N);
-//
-//     let mut insert_perm: Vec<_> = (0..N).collect();
-//     insert_perm.shuffle(&mut rng);
-//
-//     let mut get_perm: Vec<_> = (0..N).collect();
-//     let mut remove_perm: Vec<_> = (0..N).collect();
-//     remove_perm.shuffle(&mut rng);
-//
-//     println!("[Validate OrdBTree]");
-//     print!("Insertion Test: ");
-//     std::io::Write::flush(&mut std::io::stdout().lock()).unwrap();
-//     for k in 0..K {
-//         // println!("k = {}", k);
-//         let start = k * N / K;
-//         let end = start + N / K;
-//
-//         get_perm[start..end].shuffle(&mut rng);
-//
-//         for &val in insert_perm[start..end].iter() {
-//             let i = true_map.binary_search(&val).unwrap_err();
-//             true_map.insert(i, val);
-//
-//             // println!("map IS {:#?};", map);
-//             // println!("INSERT AT {} value={} INTO map;", i, val);
-//             // map.check_sizes();
-//             assert!(map.insert(i, OrdSizeOne, val).is_ok());
-//             // println!();
-//         }
-//         // println!("map IS {:#?};", map);
-//
-//         for &i in get_perm[..end].iter() {
-//             // println!("SELECT value AT {} FROM map;", i);
-//             assert_eq!(map.get(i), Some((i, &OrdSizeOne, &true_map[i])));
-//         }
-//     }
-//     println!("COMPLETE");
-//
-//     print!("Removal Test: ");
-//     std::io::Write::flush(&mut std::io::stdout().lock()).unwrap();
-//     for k in (0..K).rev() {
-//         // println!("k = {}", k);
-//         let start = k * N / K;
-//         let end = start + N / K;
-//
-//         for &val in remove_perm[start..end].iter() {
-//             let i = true_map.binary_search(&val).unwrap();
-//             assert_eq!(true_map.remove(i), val);
-//
-//             // println!("map IS {:#?};", map);
-//             // println!("REMOVE value {} AT {} FROM map;", val, i);
-//             // map.check_sizes();
-//             assert_eq!(map.remove(i), Some((OrdSizeOne, val)));
-//             // println!();
-//         }
-//
-//         for &i in get_perm[..start].iter() {
-//             // println!("SELECT value AT {} FROM map;", i);
-//             assert_eq!(map.get(i), Some((i, &OrdSizeOne, &true_map[i])));
-//         }
-//     }
-//     println!("COMPLETE");
-//     println!();
-//     println!("OrdBTree VALIDATED");
-//     println!();
-// }
+#[allow(dead_code)]
+fn validate_ord_btree_dstruct() {
+    let mut rng = SmallRng::from_entropy();
+    let mut map = OrdBTree::new();
+
+    const K: usize = 64; // 16;
+    const N: usize = K * 1024;
+    // const K: usize = 4;
+    // const N: usize = K * 8;
+
+    let mut true_map = Vec::with_capacity(N);
+
+    let mut insert_perm: Vec<_> = (0..N).collect();
+    insert_perm.shuffle(&mut rng);
+
+    let mut get_perm: Vec<_> = (0..N).collect();
+    let mut remove_perm: Vec<_> = (0..N).collect();
+    remove_perm.shuffle(&mut rng);
+
+    println!("[Validate OrdBTree]");
+    print!("Insertion Test: ");
+    std::io::Write::flush(&mut std::io::stdout().lock()).unwrap();
+    for k in 0..K {
+        // println!("k = {}", k);
+        let start = k * N / K;
+        let end = start + N / K;
+
+        get_perm[start..end].shuffle(&mut rng);
+
+        for &val in insert_perm[start..end].iter() {
+            let i = true_map.binary_search(&val).unwrap_err();
+            true_map.insert(i, val);
+
+            // println!("map IS {:#?};", map);
+            // println!("INSERT AT {} value={} INTO map;", i, val);
+            // map.check_sizes();
+            assert!(map.insert(i, OrdSizeOne, val).is_ok());
+            // println!();
+        }
+        // println!("map IS {:#?};", map);
+
+        for &i in get_perm[..end].iter() {
+            // println!("SELECT value AT {} FROM map;", i);
+            assert_eq!(map.get(i), Some((i, &OrdSizeOne, &true_map[i])));
+        }
+    }
+    println!("COMPLETE");
+
+    print!("Removal Test: ");
+    std::io::Write::flush(&mut std::io::stdout().lock()).unwrap();
+    for k in (0..K).rev() {
+        // println!("k = {}", k);
+        let start = k * N / K;
+        let end = start + N / K;
+
+        for &val in remove_perm[start..end].iter() {
+            let i = true_map.binary_search(&val).unwrap();
+            assert_eq!(true_map.remove(i), val);
+
+            // println!("map IS {:#?};", map);
+            // println!("REMOVE value {} AT {} FROM map;", val, i);
+            // map.check_sizes();
+            assert_eq!(map.remove(i), Some((OrdSizeOne, val)));
+            // println!();
+        }
+
+        for &i in get_perm[..start].iter() {
+            // println!("SELECT value AT {} FROM map;", i);
+            assert_eq!(map.get(i), Some((i, &OrdSizeOne, &true_map[i])));
+        }
+    }
+    println!("COMPLETE");
+    println!();
+    println!("OrdBTree VALIDATED");
+    println!();
+}
+
+#[allow(dead_code)]
+fn validate_ord_btree_seek_dstruct() {
+    let mut rng = SmallRng::from_entropy();
+
+    const N: usize = 4 * 1024;
+    const ALPHABET: [char; 6] = ['a', 'z', '\n', 'é', '€', '𝄞'];
+    const DIMENSIONS: [TextDimension; 4] = [
+        TextDimension::Bytes,
+        TextDimension::Chars,
+        TextDimension::Newlines,
+        TextDimension::Utf16,
+    ];
+
+    let mut true_chunks: Vec<String> = Vec::with_capacity(N);
+    let mut map = OrdBTree::new();
+
+    println!("[Validate OrdBTree::seek_by]");
+    for i in 0..N {
+        let len = rng.gen_range(1..8);
+        let chunk: String = (0..len)
+            .map(|_| *ALPHABET.choose(&mut rng).unwrap())
+            .collect();
+
+        let idx = rng.gen_range(0..=true_chunks.len());
+        let pos = true_chunks[..idx].iter().map(String::len).sum();
+        true_chunks.insert(idx, chunk.clone());
+
+        assert!(map.insert(pos, chunk, i).is_ok());
+    }
+    // Compares every cached `TextSummary`, not only their sizes.
+    map.check_invariants();
+
+    assert!(map.iter().map(|(chunk, _)| chunk).eq(true_chunks.iter()));
+    assert!(map
+        .iter()
+        .rev()
+        .map(|(chunk, _)| chunk)
+        .eq(true_chunks.iter().rev()));
+
+    let mut prefix = TextSummary::default();
+    for chunk in true_chunks.iter() {
+        let summary = TextSummary::from(chunk.as_str());
+        for &dim in DIMENSIONS.iter() {
+            for value in dim.measure(&prefix)..dim.measure(&(prefix + summary)) {
+                let (found_prefix, found_chunk, _) = map.seek_by(dim, value).unwrap();
+                assert_eq!(found_prefix, prefix);
+                assert_eq!(found_chunk, chunk);
+            }
+        }
+        prefix = prefix + summary;
+    }
+
+    assert_eq!(map.summary(), prefix);
+    for &dim in DIMENSIONS.iter() {
+        assert!(map.seek_by(dim, dim.measure(&prefix)).is_none());
+    }
+    println!("OrdBTree::seek_by VALIDATED");
+    println!();
+}
+
+#[allow(dead_code)]
+fn validate_ord_btree_range_dstruct() {
+    let mut rng = SmallRng::from_entropy();
+
+    const N: usize = 4 * 1024;
+    const K: usize = 256;
+
+    let mut true_chunks: Vec<(String, usize)> = Vec::with_capacity(N);
+    let mut map = OrdBTree::new();
+
+    println!("[Validate OrdBTree::range and OrdBTree::drain]");
+    for i in 0..N {
+        let chunk = "x".repeat(rng.gen_range(1..8));
+        let pos = true_chunks.iter().map(|(chunk, _)| chunk.len()).sum();
+        true_chunks.push((chunk.clone(), i));
+
+        assert!(map.insert(pos, chunk, i).is_ok());
+    }
+
+    for _ in 0..K {
+        let size = map.size();
+        let a = rng.gen_range(0..=size);
+        let b = rng.gen_range(a..=(a + size / 64).min(size));
+
+        let mut true_range = Vec::new();
+        let mut start = 0;
+        for (i, (chunk, value)) in true_chunks.iter().enumerate() {
+            let end = start + chunk.len();
+            if a < b && start < b && a < end {
+                true_range.push((i, start, chunk, value));
+            }
+            start = end;
+        }
+
+        assert!(map.range(a..b).eq(true_range
+            .iter()
+            .map(|&(_, start, chunk, value)| (start, chunk, value))));
+
+        let drained: Vec<_> = map.drain(a..b).collect();
+        let true_drained: Vec<_> = match (true_range.first(), true_range.last()) {
+            (Some(&(first, ..)), Some(&(last, ..))) => true_chunks.drain(first..=last).collect(),
+            _ => Vec::new(),
+        };
+        assert_eq!(drained, true_drained);
+        map.check_invariants();
+
+        assert_eq!(map.len(), true_chunks.len());
+        assert_eq!(
+            map.size(),
+            true_chunks.iter().map(|(chunk, _)| chunk.len()).sum()
+        );
+        assert!(map
+            .iter()
+            .eq(true_chunks.iter().map(|(chunk, value)| (chunk, value))));
+        assert!(map.iter().rev().eq(true_chunks
+            .iter()
+            .rev()
+            .map(|(chunk, value)| (chunk, value))));
+    }
+
+    assert_eq!(map.drain(..).count(), true_chunks.len());
+    map.check_invariants();
+    assert!(map.is_empty());
+    assert_eq!(map.size(), 0);
+    assert_eq!(map.depth(), 1);
+    println!("OrdBTree::range and OrdBTree::drain VALIDATED");
+    println!();
+}
+
+#[allow(dead_code)]
+fn validate_ord_btree_splice_dstruct() {
+    let mut rng = SmallRng::from_entropy();
+
+    const K: usize = 512;
+
+    let mut true_chunks: Vec<(String, usize)> = Vec::new();
+    let mut map = OrdBTree::new();
+    let mut next_value = 0;
+    let mut gen_chunks = |rng: &mut SmallRng, len: usize| -> Vec<(String, usize)> {
+        (0..len)
+            .map(|_| {
+                next_value += 1;
+                ("x".repeat(rng.gen_range(1..8)), next_value)
+            })
+            .collect()
+    };
+
+    println!("[Validate OrdBTree::insert_many, OrdBTree::splice and OrdBTree::replace]");
+    for _ in 0..K {
+        let idx = rng.gen_range(0..=true_chunks.len());
+        let pos: usize = true_chunks[..idx]
+            .iter()
+            .map(|(chunk, _)| chunk.len())
+            .sum();
+
+        match rng.gen_range(0..4) {
+            0 | 1 => {
+                let max_len = *[4, 64, 1024].choose(&mut rng).unwrap();
+                let len = rng.gen_range(0..max_len);
+                let chunks = gen_chunks(&mut rng, len);
+                true_chunks.splice(idx..idx, chunks.iter().cloned());
+                assert!(map.insert_many(pos, chunks).is_ok());
+
+                if idx < true_chunks.len() && 1 < true_chunks[idx].0.len() {
+                    assert!(map.insert_many(pos + 1, Vec::new()).is_err());
+                }
+            }
+            2 => {
+                let end_idx = rng.gen_range(idx..=(idx + 64).min(true_chunks.len()));
+                let end = pos
+                    + true_chunks[idx..end_idx]
+                        .iter()
+                        .map(|(chunk, _)| chunk.len())
+                        .sum::<usize>();
+                let len = rng.gen_range(0..64);
+                let chunks = gen_chunks(&mut rng, len);
+
+                let true_removed: Vec<_> = true_chunks
+                    .splice(idx..end_idx, chunks.iter().cloned())
+                    .collect();
+                let removed: Vec<_> = map.splice(pos..end, chunks).collect();
+                assert_eq!(removed, true_removed);
+            }
+            _ => {
+                let chunks = gen_chunks(&mut rng, 1);
+                let (chunk, value) = chunks.into_iter().next().unwrap();
+
+                if idx < true_chunks.len() {
+                    let true_replaced =
+                        std::mem::replace(&mut true_chunks[idx], (chunk.clone(), value));
+                    assert_eq!(map.replace(pos, chunk, value), Ok(true_replaced));
+                } else {
+                    assert!(map.replace(pos, chunk, value).is_err());
+                }
+            }
+        }
+        map.check_invariants();
+
+        assert_eq!(map.len(), true_chunks.len());
+        assert_eq!(
+            map.size(),
+            true_chunks.iter().map(|(chunk, _)| chunk.len()).sum()
+        );
+        assert!(map
+            .iter()
+            .eq(true_chunks.iter().map(|(chunk, value)| (chunk, value))));
+    }
+    println!("OrdBTree::insert_many, OrdBTree::splice and OrdBTree::replace VALIDATED");
+    println!();
+}
 
 #[allow(dead_code)]
 fn bench_btree_dstruct() {
@@ -372,45 +2015,45 @@ fn bench_btree_dstruct() {
     });
 }
 
-// #[allow(dead_code)]
-// fn bench_ord_btree_dstruct() {
-//     let mut rng = SmallRng::from_entropy();
-//
-//     const N: usize = 256 * 1024; // 256 KiB
-//
-//     let (insert_perm, get_perm, remove_perm) = {
-//         let insert_perm: Vec<_> = (0..N).map(|n| rng.gen_range(0..=n)).collect();
-//         let remove_perm: Vec<_> = (1..=N).rev().map(|n| rng.gen_range(0..n)).collect();
-//         let mut get_perm: Vec<_> = (0..N).collect();
-//
-//         get_perm.shuffle(&mut rng);
-//
-//         (insert_perm, get_perm, remove_perm)
-//     };
-//
-//     drop(get_perm.iter().collect::<std::collections::BTreeSet<_>>());
-//
-//     let mut map = OrdBTree::new();
-//     bench("OrdBTree::insert", 4, || {
-//         for (len, &i) in insert_perm.iter().enumerate() {
-//             assert_eq!(map.len(), len);
-//             assert!(map.insert(i, OrdSizeOne, len).is_ok()); // lets hope it's correct (:
-//             assert_eq!(map.len(), len + 1);
-//         }
-//     });
-//     bench("OrdBTree::get", 5, || {
-//         for &i in get_perm.iter() {
-//             assert!(map.get(i).is_some()); // lets hope it's correct (:
-//         }
-//     });
-//     bench("OrdBTree::remove", 4, || {
-//         for (len, &i) in remove_perm.iter().enumerate() {
-//             assert_eq!(map.len(), N - len);
-//             assert!(map.remove(i).is_some()); // lets hope it's correct (:
-//             assert_eq!(map.len(), N - len - 1);
-//         }
-//     });
-// }
+#[allow(dead_code)]
+fn bench_ord_btree_dstruct() {
+    let mut rng = SmallRng::from_entropy();
+
+    const N: usize = 256 * 1024; // 256 KiB
+
+    let (insert_perm, get_perm, remove_perm) = {
+        let insert_perm: Vec<_> = (0..N).map(|n| rng.gen_range(0..=n)).collect();
+        let remove_perm: Vec<_> = (1..=N).rev().map(|n| rng.gen_range(0..n)).collect();
+        let mut get_perm: Vec<_> = (0..N).collect();
+
+        get_perm.shuffle(&mut rng);
+
+        (insert_perm, get_perm, remove_perm)
+    };
+
+    drop(get_perm.iter().collect::<std::collections::BTreeSet<_>>());
+
+    let mut map = OrdBTree::new();
+    bench("OrdBTree::insert", 4, || {
+        for (len, &i) in insert_perm.iter().enumerate() {
+            assert_eq!(map.len(), len);
+            assert!(map.insert(i, OrdSizeOne, len).is_ok()); // lets hope it's correct (:
+            assert_eq!(map.len(), len + 1);
+        }
+    });
+    bench("OrdBTree::get", 5, || {
+        for &i in get_perm.iter() {
+            assert!(map.get(i).is_some()); // lets hope it's correct (:
+        }
+    });
+    bench("OrdBTree::remove", 4, || {
+        for (len, &i) in remove_perm.iter().enumerate() {
+            assert_eq!(map.len(), N - len);
+            assert!(map.remove(i).is_some()); // lets hope it's correct (:
+            assert_eq!(map.len(), N - len - 1);
+        }
+    });
+}
 
 #[allow(dead_code)]
 fn valgrind_btree_dstruct() {
@@ -450,9 +2093,477 @@ fn valgrind_btree_dstruct() {
 #[test]
 pub fn main() {
     // validate_btree_dstruct();
-    // validate_ord_btree_dstruct();
+    validate_ord_btree_dstruct();
+    validate_ord_btree_seek_dstruct();
+    validate_ord_btree_range_dstruct();
+    validate_ord_btree_splice_dstruct();
+    validate_segment_tree_dstruct();
+    validate_segment_tree_search_dstruct();
+    validate_segment_tree_query_dstruct();
+    validate_segment_tree_resize_dstruct();
+    validate_sparse_segment_tree_dstruct();
+    validate_persistent_segment_tree_dstruct();
+    validate_2d_trees_dstruct();
+    validate_beats_segment_tree_dstruct();
+    validate_wavelet_matrix_dstruct();
+    validate_range_fenwick_tree_dstruct();
+    validate_fenwick_tree_lower_bound_dstruct();
+    validate_fenwick_tree_build_dstruct();
+    validate_compressed_fenwick_tree_dstruct();
+    validate_indexed_multiset_dstruct();
+    validate_groups_dstruct();
+    validate_group_products_dstruct();
+    validate_fn_groups_dstruct();
+    validate_group_laws_dstruct();
+    validate_mod_int_dstruct();
+    validate_semirings_dstruct();
+    validate_lazy_segment_tree_dstruct();
     bench_btree_dstruct();
     println!();
     // bench_ord_btree_dstruct();
     // valgrind_btree_dstruct();
 }
+
+/// Differential tests of `OrdBTree` against a `Vec` with prefix-summed sizes.
+#[cfg(test)]
+mod ord_btree_model {
+    use crate::ord_btree::{MAX_NUM_ELEMENTS, MIN_NUM_ELEMENTS};
+    use crate::{OrdBTree, OrdSize, OrdSummary};
+    use proptest::prelude::*;
+    use std::ops::Add;
+
+    /// The size together with the largest size, which can't be subtracted back out, so the
+    /// tree has to recalculate its summaries whenever elements leave a node.
+    #[derive(Debug, Clone, Copy, Default, PartialEq)]
+    struct SizeMax {
+        size: usize,
+        max: usize,
+    }
+
+    impl Add for SizeMax {
+        type Output = Self;
+
+        fn add(self, rhs: Self) -> Self {
+            Self {
+                size: self.size + rhs.size,
+                max: self.max.max(rhs.max),
+            }
+        }
+    }
+
+    impl OrdSummary for SizeMax {
+        fn size(&self) -> usize {
+            self.size
+        }
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct SizedKey(usize);
+
+    impl OrdSize for SizedKey {
+        type Summary = SizeMax;
+
+        fn summary(&self) -> SizeMax {
+            SizeMax {
+                size: self.0,
+                max: self.0,
+            }
+        }
+    }
+
+    #[derive(Debug, Clone)]
+    enum ModelOp {
+        /// Insert before the element `at % (len + 1)`, or inside of it if `inside`.
+        Insert {
+            at: usize,
+            inside: bool,
+            size: usize,
+        },
+        /// Remove the element `at % len`, or try to remove from inside of it if `inside`.
+        Remove {
+            at: usize,
+            inside: bool,
+        },
+        Get {
+            pos: usize,
+        },
+        /// Drain the positions `start..start + len`, both taken modulo what fits.
+        Drain {
+            start: usize,
+            len: usize,
+        },
+        /// Replace the elements `at..at + count` (modulo what fits) with elements of `sizes`,
+        /// starting inside of the first element if `inside`.
+        Splice {
+            at: usize,
+            count: usize,
+            inside: bool,
+            sizes: Vec<usize>,
+        },
+        /// Insert elements of `sizes` before the element `at % (len + 1)`, or try to insert
+        /// inside of it if `inside`.
+        InsertMany {
+            at: usize,
+            inside: bool,
+            sizes: Vec<usize>,
+        },
+        /// Drain the whole tree and build a new one out of its elements and elements of `sizes`.
+        Build {
+            sizes: Vec<usize>,
+        },
+    }
+
+    #[derive(Default)]
+    struct Model {
+        tree: OrdBTree<SizedKey, usize>,
+        elements: Vec<(SizedKey, usize)>,
+        next_value: usize,
+    }
+
+    impl Model {
+        fn prefix_sums(&self) -> Vec<usize> {
+            let mut prefix_sums = Vec::with_capacity(self.elements.len() + 1);
+            prefix_sums.push(0);
+            for (key, _) in self.elements.iter() {
+                prefix_sums.push(prefix_sums.last().unwrap() + key.0);
+            }
+            prefix_sums
+        }
+
+        fn insert(&mut self, idx: usize, inside: bool, size: usize) {
+            let prefix_sums = self.prefix_sums();
+            self.next_value += 1;
+
+            if inside && idx < self.elements.len() && 1 < self.elements[idx].0 .0 {
+                let pos = prefix_sums[idx] + 1;
+                let result = self.tree.insert(pos, SizedKey(size), self.next_value);
+                assert_eq!(result, Err((SizedKey(size), self.next_value)));
+            } else {
+                let pos = prefix_sums[idx];
+                assert!(self
+                    .tree
+                    .insert(pos, SizedKey(size), self.next_value)
+                    .is_ok());
+                self.elements.insert(idx, (SizedKey(size), self.next_value));
+            }
+        }
+
+        fn remove(&mut self, idx: usize, inside: bool) {
+            let prefix_sums = self.prefix_sums();
+
+            if inside && 1 < self.elements[idx].0 .0 {
+                assert_eq!(self.tree.remove(prefix_sums[idx] + 1), None);
+            } else {
+                let removed = self.tree.remove(prefix_sums[idx]);
+                assert_eq!(removed, Some(self.elements.remove(idx)));
+            }
+        }
+
+        fn get(&self, pos: usize) {
+            let prefix_sums = self.prefix_sums();
+
+            let expected = match prefix_sums[1..].iter().position(|&end| pos < end) {
+                Some(idx) => {
+                    let (key, value) = &self.elements[idx];
+                    Some((prefix_sums[idx], key, value))
+                }
+                None => None,
+            };
+            assert_eq!(self.tree.get(pos), expected);
+        }
+
+        fn new_pairs(&mut self, sizes: &[usize]) -> Vec<(SizedKey, usize)> {
+            sizes
+                .iter()
+                .map(|&size| {
+                    self.next_value += 1;
+                    (SizedKey(size), self.next_value)
+                })
+                .collect()
+        }
+
+        fn drain(&mut self, start: usize, end: usize) {
+            let prefix_sums = self.prefix_sums();
+            let overlapping: Vec<usize> = (0..self.elements.len())
+                .filter(|&i| start < end && prefix_sums[i] < end && start < prefix_sums[i + 1])
+                .collect();
+            let expected: Vec<_> = match (overlapping.first(), overlapping.last()) {
+                (Some(&first), Some(&last)) => self.elements.drain(first..=last).collect(),
+                _ => Vec::new(),
+            };
+            assert!(self.tree.drain(start..end).eq(expected));
+        }
+
+        fn splice(&mut self, idx: usize, end_idx: usize, inside: bool, sizes: &[usize]) {
+            let prefix_sums = self.prefix_sums();
+            let pairs = self.new_pairs(sizes);
+
+            let mut start = prefix_sums[idx];
+            if inside && idx < end_idx && 1 < self.elements[idx].0 .0 {
+                start += 1;
+            }
+            let removed: Vec<_> = self
+                .tree
+                .splice(start..prefix_sums[end_idx], pairs.iter().cloned())
+                .collect();
+            let expected: Vec<_> = self.elements.splice(idx..end_idx, pairs).collect();
+            assert_eq!(removed, expected);
+        }
+
+        fn insert_many(&mut self, idx: usize, inside: bool, sizes: &[usize]) {
+            let prefix_sums = self.prefix_sums();
+            let pairs = self.new_pairs(sizes);
+
+            if inside && idx < self.elements.len() && 1 < self.elements[idx].0 .0 {
+                let result = self.tree.insert_many(prefix_sums[idx] + 1, pairs.clone());
+                assert_eq!(result, Err(pairs));
+            } else {
+                assert!(self
+                    .tree
+                    .insert_many(prefix_sums[idx], pairs.iter().cloned())
+                    .is_ok());
+                self.elements.splice(idx..idx, pairs);
+            }
+        }
+
+        fn build(&mut self, sizes: &[usize]) {
+            let mut pairs: Vec<_> = self.tree.drain(..).collect();
+            assert_eq!(pairs, self.elements);
+            pairs.extend(self.new_pairs(sizes));
+
+            self.tree = OrdBTree::default();
+            assert!(self.tree.insert_many(0, pairs.iter().cloned()).is_ok());
+            self.elements = pairs;
+        }
+
+        fn apply(&mut self, op: &ModelOp) {
+            match *op {
+                ModelOp::Insert { at, inside, size } => {
+                    self.insert(at % (self.elements.len() + 1), inside, size)
+                }
+                ModelOp::Remove { at, inside } => {
+                    if !self.elements.is_empty() {
+                        self.remove(at % self.elements.len(), inside);
+                    }
+                }
+                ModelOp::Get { pos } => self.get(pos % (self.tree.size() + 2)),
+                ModelOp::Drain { start, len } => {
+                    let start = start % (self.tree.size() + 1);
+                    let end = start + len % (self.tree.size() - start + 1);
+                    self.drain(start, end);
+                }
+                ModelOp::Splice {
+                    at,
+                    count,
+                    inside,
+                    ref sizes,
+                } => {
+                    let idx = at % (self.elements.len() + 1);
+                    let end_idx = idx + count % (self.elements.len() - idx + 1);
+                    self.splice(idx, end_idx, inside, sizes);
+                }
+                ModelOp::InsertMany {
+                    at,
+                    inside,
+                    ref sizes,
+                } => self.insert_many(at % (self.elements.len() + 1), inside, sizes),
+                ModelOp::Build { ref sizes } => self.build(sizes),
+            }
+        }
+
+        fn check(&self) {
+            self.tree.check_invariants();
+
+            let prefix_sums = self.prefix_sums();
+            assert_eq!(self.tree.len(), self.elements.len());
+            assert_eq!(self.tree.size(), *prefix_sums.last().unwrap());
+            assert_eq!(
+                self.tree.summary().max,
+                self.elements
+                    .iter()
+                    .map(|(key, _)| key.0)
+                    .max()
+                    .unwrap_or(0)
+            );
+            assert!(self
+                .tree
+                .iter()
+                .eq(self.elements.iter().map(|(key, value)| (key, value))));
+
+            for (i, (key, value)) in self.elements.iter().enumerate() {
+                assert_eq!(
+                    self.tree.get(prefix_sums[i]),
+                    Some((prefix_sums[i], key, value))
+                );
+            }
+            assert_eq!(self.tree.get(self.tree.size()), None);
+        }
+    }
+
+    fn sizes() -> impl Strategy<Value = Vec<usize>> {
+        prop::collection::vec(1..4usize, 0..16)
+    }
+
+    fn model_op() -> impl Strategy<Value = ModelOp> {
+        prop_oneof![
+            4 => (any::<usize>(), prop::bool::weighted(0.1), 1..4usize)
+                .prop_map(|(at, inside, size)| ModelOp::Insert { at, inside, size }),
+            2 => (any::<usize>(), prop::bool::weighted(0.1))
+                .prop_map(|(at, inside)| ModelOp::Remove { at, inside }),
+            1 => any::<usize>().prop_map(|pos| ModelOp::Get { pos }),
+            1 => (any::<usize>(), 0..64usize)
+                .prop_map(|(start, len)| ModelOp::Drain { start, len }),
+            1 => (any::<usize>(), 0..16usize, prop::bool::weighted(0.2), sizes())
+                .prop_map(|(at, count, inside, sizes)| ModelOp::Splice { at, count, inside, sizes }),
+            1 => (any::<usize>(), prop::bool::weighted(0.1), sizes())
+                .prop_map(|(at, inside, sizes)| ModelOp::InsertMany { at, inside, sizes }),
+            1 => prop::collection::vec(1..4usize, 0..8).prop_map(|sizes| ModelOp::Build { sizes }),
+        ]
+    }
+
+    proptest! {
+        #[test]
+        fn ord_btree_matches_vec(ops in prop::collection::vec(model_op(), 0..1024)) {
+            let mut model = Model::default();
+            for op in ops.iter() {
+                model.apply(op);
+                model.check();
+            }
+        }
+    }
+
+    /// Removes and inserts at every position of trees whose nodes are at, or right next to,
+    /// `MIN_NUM_ELEMENTS` and `MAX_NUM_ELEMENTS`, so every borrow, merge and split path is taken.
+    #[test]
+    fn ord_btree_node_thresholds() {
+        let min_num_children = MIN_NUM_ELEMENTS + 1;
+        let max_num_children = MAX_NUM_ELEMENTS + 1;
+
+        let mut lens: Vec<usize> = (0..=3 * max_num_children).collect();
+        for &num_children in [min_num_children, max_num_children].iter() {
+            let len = num_children * num_children - 1;
+            lens.extend_from_slice(&[len - 1, len, len + 1]);
+        }
+
+        // Pushing to the back and to the front leave the nodes at different fill levels.
+        let builders: [fn(&mut Model, usize); 2] = [
+            |model, i| model.insert(i, false, 1 + i % 3),
+            |model, i| model.insert(0, false, 1 + i % 3),
+        ];
+
+        for &len in lens.iter() {
+            for build in builders.iter() {
+                for idx in 0..=len {
+                    let mut model = Model::default();
+                    for i in 0..len {
+                        build(&mut model, i);
+                    }
+                    model.check();
+
+                    if idx < len {
+                        model.remove(idx, false);
+                        model.check();
+                    }
+                    model.insert(idx.min(model.elements.len()), false, 2);
+                    model.check();
+                }
+            }
+        }
+    }
+}
+
+#[test]
+#[should_panic(expected = "index (0, 3) is out of bounds for size (4, 3)")]
+fn segment_tree_2d_get_out_of_bounds() {
+    let st = SegmentTree2D::new(groups::NumAdditiveGroups::<i64>::new(), 4, 3);
+    st.get(0, 3);
+}
+
+#[test]
+#[should_panic(expected = "range is out of bounds for length 4")]
+fn segment_tree_query_overflow() {
+    let st = SegmentTree::new(groups::NumAdditiveGroups::<i64>::new(), 4);
+    st.query(..=usize::MAX);
+}
+
+#[test]
+#[should_panic(expected = "6 is not invertible modulo 15")]
+fn mod_int_composite_inverse() {
+    use groups::{Invertibillity, NumMultiplicative};
+
+    NumMultiplicative::<ModInt<15>>::new().inv(ModInt::new(6));
+}
+
+#[test]
+#[should_panic(expected = "range is out of bounds for length 4")]
+fn lazy_segment_tree_sum_overflow() {
+    use groups::{Associativity, Identity, Magma, MonoidAction, NumAdditiveGroups};
+    use std::ops::Bound;
+
+    /// The action that leaves every element as it is.
+    struct NoAction;
+    impl Magma for NoAction {
+        type Elem = ();
+        fn add(&self, _: (), _: ()) {}
+    }
+    impl Identity for NoAction {
+        fn id(&self) {}
+    }
+    impl Associativity for NoAction {}
+    impl MonoidAction<NumAdditiveGroups<i64>> for NoAction {
+        fn act(&self, _: (), x: i64) -> i64 {
+            x
+        }
+    }
+
+    let st = LazySegmentTree::new(NumAdditiveGroups::<i64>::new(), NoAction, 4);
+    st.sum((Bound::Excluded(usize::MAX), Bound::Unbounded));
+}
+
+#[test]
+#[should_panic(expected = "range is out of bounds for length 4")]
+fn beats_segment_tree_sum_overflow() {
+    let st = BeatsSegmentTree::<i64>::new(4);
+    st.sum(..=usize::MAX);
+}
+
+#[test]
+#[should_panic(expected = "range is out of bounds for length 4")]
+fn wavelet_matrix_quantile_overflow() {
+    let wm = WaveletMatrix::new(vec![3, 1, 4, 1]);
+    wm.quantile(..=usize::MAX, 0);
+}
+
+#[test]
+#[should_panic(expected = "range is out of bounds for length 4")]
+fn range_fenwick_tree_sum_overflow() {
+    let ft = RangeFenwickTree::new(groups::NumAdditiveGroups::<i64>::new(), 4);
+    ft.range_sum(..=usize::MAX);
+}
+
+#[test]
+#[should_panic(expected = "range is out of bounds for length 4")]
+fn fenwick_tree_range_sum_overflow() {
+    use std::ops::Bound;
+
+    let mut ft = FenwickTree::new(groups::NumAdditiveGroups::<i64>::new());
+    ft.resize_id(4);
+    ft.range_sum((Bound::Excluded(usize::MAX), Bound::Unbounded));
+}
+
+#[test]
+#[should_panic(expected = "range is out of bounds for length 4")]
+fn ord_btree_range_overflow() {
+    let mut map = OrdBTree::new();
+    for i in 0..4 {
+        assert!(map.insert(i, OrdSizeOne, i).is_ok());
+    }
+    map.range(..=usize::MAX).count();
+}
+
+#[test]
+#[should_panic(expected = "`iter` is longer than the tree")]
+fn persistent_segment_tree_build_too_long() {
+    let mut st = PersistentSegmentTree::new(groups::NumAdditiveGroups::<i64>::new(), 4);
+    st.build(0..5);
+}
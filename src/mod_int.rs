@@ -0,0 +1,440 @@
+use crate::groups::{Invertibillity, NumAdditiveGroups, NumMultiplicative, ScalarMultiplication};
+use std::fmt;
+use std::hash::Hash;
+use std::iter::{Product, Sum};
+use std::marker::PhantomData;
+use std::ops;
+
+/// Montgomery multiplication modulo an odd `modulus` smaller than `2^63`, where `x` is represented
+/// by `x * 2^64 mod modulus`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Montgomery {
+    modulus: u64,
+    /// `-modulus^-1 mod 2^64`
+    neg_inv: u64,
+    /// `2^128 mod modulus`
+    r2: u64,
+}
+
+impl Montgomery {
+    pub const fn new(modulus: u64) -> Self {
+        assert!(modulus % 2 == 1, "the modulus has to be odd");
+        assert!(modulus < 1 << 63, "the modulus has to be smaller than 2^63");
+
+        // Every step doubles the number of correct low bits, starting from 3.
+        let mut inv = modulus;
+        let mut i = 0;
+        while i < 5 {
+            inv = inv.wrapping_mul(2u64.wrapping_sub(modulus.wrapping_mul(inv)));
+            i += 1;
+        }
+
+        let m = modulus as u128;
+        Self {
+            modulus,
+            neg_inv: inv.wrapping_neg(),
+            r2: ((u128::MAX % m + 1) % m) as u64,
+        }
+    }
+
+    #[inline]
+    pub const fn modulus(&self) -> u64 {
+        self.modulus
+    }
+
+    /// `t * 2^-64 mod modulus`, for `t < modulus * 2^64`.
+    #[inline]
+    pub const fn reduce(&self, t: u128) -> u64 {
+        let m = (t as u64).wrapping_mul(self.neg_inv);
+        let u = ((t + m as u128 * self.modulus as u128) >> 64) as u64;
+        if u >= self.modulus {
+            u - self.modulus
+        } else {
+            u
+        }
+    }
+
+    /// Multiplies two numbers in Montgomery form.
+    #[inline]
+    pub const fn mul(&self, a: u64, b: u64) -> u64 {
+        self.reduce(a as u128 * b as u128)
+    }
+
+    /// Converts `x < modulus` to Montgomery form.
+    #[inline]
+    pub const fn to_montgomery(&self, x: u64) -> u64 {
+        self.mul(x, self.r2)
+    }
+
+    /// Converts `x` back from Montgomery form.
+    #[inline]
+    pub const fn from_montgomery(&self, x: u64) -> u64 {
+        self.reduce(x as u128)
+    }
+}
+
+/// The modulus of a `Modular`, which is known either at compile time (`ConstModulus`) or at
+/// runtime (see `dyn_modulus!`).
+pub trait Modulus: Copy + Eq + Hash + 'static {
+    fn montgomery() -> Montgomery;
+
+    #[inline]
+    fn modulus() -> u64 {
+        Self::montgomery().modulus()
+    }
+}
+
+/// The modulus `P`, which has to be odd and smaller than `2^63`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct ConstModulus<const P: u64>;
+
+impl<const P: u64> ConstModulus<P> {
+    const MONTGOMERY: Montgomery = Montgomery::new(P);
+}
+
+impl<const P: u64> Modulus for ConstModulus<P> {
+    #[inline]
+    fn montgomery() -> Montgomery {
+        Self::MONTGOMERY
+    }
+}
+
+/// Declares a unit struct which is a `Modulus` set at runtime with `set`, for each thread
+/// separately.
+#[macro_export]
+macro_rules! dyn_modulus {
+    ($(#[$attr:meta])* $vis:vis struct $name:ident;) => {
+        $(#[$attr])*
+        #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+        $vis struct $name;
+
+        const _: () = {
+            ::std::thread_local! {
+                static MONTGOMERY: ::std::cell::Cell<Option<$crate::Montgomery>> =
+                    ::std::cell::Cell::new(None);
+            }
+
+            impl $name {
+                /// Sets the modulus, which has to be odd and smaller than `2^63`. The elements
+                /// created with the previous modulus become meaningless.
+                pub fn set(modulus: u64) {
+                    MONTGOMERY.with(|m| m.set(Some($crate::Montgomery::new(modulus))));
+                }
+            }
+
+            impl $crate::Modulus for $name {
+                #[inline]
+                fn montgomery() -> $crate::Montgomery {
+                    MONTGOMERY
+                        .with(|m| m.get())
+                        .expect(concat!("the modulus of `", stringify!($name), "` isn't set"))
+                }
+            }
+        };
+    };
+}
+
+dyn_modulus! {
+    /// The default modulus of `DynModInt`.
+    pub struct DynModulus;
+}
+
+/// An integer modulo `M::modulus()`, stored in Montgomery form so multiplication doesn't need a
+/// division.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Modular<M: Modulus> {
+    value: u64,
+    _modulus: PhantomData<M>,
+}
+
+/// An integer modulo `P`, which has to be odd and smaller than `2^63`.
+pub type ModInt<const P: u64> = Modular<ConstModulus<P>>;
+
+/// An integer modulo a modulus set at runtime, see `dyn_modulus!`.
+pub type DynModInt<M = DynModulus> = Modular<M>;
+
+impl<M: Modulus> Modular<M> {
+    /// `x mod M::modulus()`
+    #[inline]
+    pub fn new(x: u64) -> Self {
+        let mont = M::montgomery();
+        Self::from_raw(mont.to_montgomery(x % mont.modulus()))
+    }
+
+    /// Wraps `value`, which is already in Montgomery form.
+    #[inline]
+    fn from_raw(value: u64) -> Self {
+        Self {
+            value,
+            _modulus: PhantomData,
+        }
+    }
+
+    #[inline]
+    pub fn modulus() -> u64 {
+        M::modulus()
+    }
+
+    /// The representative in `0..M::modulus()`.
+    #[inline]
+    pub fn value(self) -> u64 {
+        M::montgomery().from_montgomery(self.value)
+    }
+
+    /// O(log n)
+    pub fn pow(mut self, mut n: u64) -> Self {
+        let mut acc = num::one::<Self>();
+        while n != 0 {
+            if n & 1 != 0 {
+                acc *= self;
+            }
+            self *= self;
+            n >>= 1;
+        }
+        acc
+    }
+
+    /// The multiplicative inverse, or `None` if `self` isn't coprime to the modulus.
+    ///
+    /// O(log m)
+    pub fn try_inv(self) -> Option<Self> {
+        // The extended Euclidean algorithm, keeping `s * self = a (mod m)`.
+        let m = Self::modulus() as i128;
+        let (mut a, mut b) = (self.value() as i128, m);
+        let (mut s, mut t) = (1i128, 0i128);
+        while b != 0 {
+            let q = a / b;
+            (a, b) = (b, a - q * b);
+            (s, t) = (t, s - q * t);
+        }
+        (a == 1).then(|| Self::new(s.rem_euclid(m) as u64))
+    }
+
+    /// O(log m)
+    pub fn inv(self) -> Self {
+        match self.try_inv() {
+            Some(inv) => inv,
+            None => panic!(
+                "{} is not invertible modulo {}",
+                self.value(),
+                Self::modulus()
+            ),
+        }
+    }
+}
+
+impl<M: Modulus> Default for Modular<M> {
+    #[inline]
+    fn default() -> Self {
+        Self::from_raw(0)
+    }
+}
+
+impl<M: Modulus> fmt::Debug for Modular<M> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&self.value(), f)
+    }
+}
+
+impl<M: Modulus> fmt::Display for Modular<M> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.value(), f)
+    }
+}
+
+macro_rules! from_unsigned {
+    ($($t:ty),*) => {
+        $(
+            impl<M: Modulus> From<$t> for Modular<M> {
+                #[inline]
+                fn from(x: $t) -> Self {
+                    Self::new(x as u64)
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! from_signed {
+    ($($t:ty),*) => {
+        $(
+            impl<M: Modulus> From<$t> for Modular<M> {
+                #[inline]
+                fn from(x: $t) -> Self {
+                    let abs = Self::new(x.unsigned_abs() as u64);
+                    if x < 0 {
+                        -abs
+                    } else {
+                        abs
+                    }
+                }
+            }
+        )*
+    };
+}
+
+from_unsigned!(u8, u16, u32, u64, usize);
+from_signed!(i8, i16, i32, i64, isize);
+
+impl<M: Modulus> ops::Add for Modular<M> {
+    type Output = Self;
+
+    #[inline]
+    fn add(self, rhs: Self) -> Self {
+        let m = M::modulus();
+        let sum = self.value + rhs.value;
+        Self::from_raw(if sum >= m { sum - m } else { sum })
+    }
+}
+
+impl<M: Modulus> ops::Sub for Modular<M> {
+    type Output = Self;
+
+    #[inline]
+    fn sub(self, rhs: Self) -> Self {
+        if self.value >= rhs.value {
+            Self::from_raw(self.value - rhs.value)
+        } else {
+            Self::from_raw(self.value + M::modulus() - rhs.value)
+        }
+    }
+}
+
+impl<M: Modulus> ops::Mul for Modular<M> {
+    type Output = Self;
+
+    #[inline]
+    fn mul(self, rhs: Self) -> Self {
+        Self::from_raw(M::montgomery().mul(self.value, rhs.value))
+    }
+}
+
+impl<M: Modulus> ops::Div for Modular<M> {
+    type Output = Self;
+
+    /// Panics if `rhs` isn't invertible.
+    #[inline]
+    #[allow(clippy::suspicious_arithmetic_impl)]
+    fn div(self, rhs: Self) -> Self {
+        self * rhs.inv()
+    }
+}
+
+impl<M: Modulus> ops::Rem for Modular<M> {
+    type Output = Self;
+
+    /// `self - (self / rhs) * rhs`, which is always zero because every invertible `rhs` divides
+    /// `self`. Panics like `div` otherwise.
+    #[inline]
+    fn rem(self, rhs: Self) -> Self {
+        self - self / rhs * rhs
+    }
+}
+
+impl<M: Modulus> ops::Neg for Modular<M> {
+    type Output = Self;
+
+    #[inline]
+    fn neg(self) -> Self {
+        Self::default() - self
+    }
+}
+
+macro_rules! assign_ops {
+    ($($trait:ident $fn:ident $op:tt),*) => {
+        $(
+            impl<M: Modulus> ops::$trait for Modular<M> {
+                #[inline]
+                fn $fn(&mut self, rhs: Self) {
+                    *self = *self $op rhs;
+                }
+            }
+        )*
+    };
+}
+
+assign_ops!(
+    AddAssign add_assign +,
+    SubAssign sub_assign -,
+    MulAssign mul_assign *,
+    DivAssign div_assign /,
+    RemAssign rem_assign %
+);
+
+impl<M: Modulus> Sum for Modular<M> {
+    fn sum<Iter: Iterator<Item = Self>>(iter: Iter) -> Self {
+        iter.fold(Self::default(), |acc, x| acc + x)
+    }
+}
+
+impl<M: Modulus> Product for Modular<M> {
+    fn product<Iter: Iterator<Item = Self>>(iter: Iter) -> Self {
+        iter.fold(num::one(), |acc, x| acc * x)
+    }
+}
+
+impl<M: Modulus> num::Zero for Modular<M> {
+    #[inline]
+    fn zero() -> Self {
+        Self::default()
+    }
+
+    #[inline]
+    fn is_zero(&self) -> bool {
+        self.value == 0
+    }
+}
+
+impl<M: Modulus> num::One for Modular<M> {
+    #[inline]
+    fn one() -> Self {
+        Self::new(1)
+    }
+}
+
+impl<M: Modulus> num::Num for Modular<M> {
+    type FromStrRadixErr = std::num::ParseIntError;
+
+    fn from_str_radix(s: &str, radix: u32) -> Result<Self, Self::FromStrRadixErr> {
+        match s.strip_prefix('-') {
+            Some(s) => u64::from_str_radix(s, radix).map(|x| -Self::new(x)),
+            None => u64::from_str_radix(s, radix).map(Self::new),
+        }
+    }
+}
+
+/// Addition of integers modulo `M::modulus()`, which is an abelian group.
+impl<M: Modulus> Invertibillity for NumAdditiveGroups<Modular<M>> {
+    #[inline]
+    fn inv(&self, x: Modular<M>) -> Modular<M> {
+        -x
+    }
+
+    #[inline]
+    fn sub(&self, lhs: Modular<M>, rhs: Modular<M>) -> Modular<M> {
+        lhs - rhs
+    }
+}
+
+impl<M: Modulus> ScalarMultiplication for NumAdditiveGroups<Modular<M>> {
+    #[inline]
+    fn mul_scalar(&self, x: Modular<M>, n: usize) -> Modular<M> {
+        x * Modular::from(n)
+    }
+}
+
+/// Multiplication of integers modulo `M::modulus()`, which is a group without zero only for a
+/// prime modulus. For a composite modulus, `inv` and `sub` panic on the elements which aren't
+/// coprime to it, instead of returning a wrong inverse.
+impl<M: Modulus> Invertibillity for NumMultiplicative<Modular<M>> {
+    /// Panics if `gcd(x, M::modulus()) != 1`.
+    #[inline]
+    fn inv(&self, x: Modular<M>) -> Modular<M> {
+        x.inv()
+    }
+
+    #[inline]
+    fn sub(&self, lhs: Modular<M>, rhs: Modular<M>) -> Modular<M> {
+        lhs / rhs
+    }
+}
@@ -2,18 +2,20 @@
 
 // TODO: Optimize the number of moves (and copies potentialy?) of StackVec based structs.
 
-use crate::{OnStackRefMutStack, OuterLenStackVec, RefMutStack, StackVec, StackVecIntoIter};
+use crate::{
+    segment_tree::bounds, OnStackRefMutStack, OuterLenStackVec, StackVec, StackVecIntoIter,
+};
 use std::{
     cmp::Ordering,
     fmt, mem,
-    ops::{Deref, DerefMut},
-    ptr,
+    ops::{Add, RangeBounds},
+    ptr, vec,
 };
 
 const B: usize = 6;
 
-const MIN_NUM_ELEMENTS: usize = B - 1;
-const MAX_NUM_ELEMENTS: usize = 2 * B - 1;
+pub(crate) const MIN_NUM_ELEMENTS: usize = B - 1;
+pub(crate) const MAX_NUM_ELEMENTS: usize = 2 * B - 1;
 const MIN_NUM_CHILDREN: usize = B;
 const MAX_NUM_CHILDREN: usize = 2 * B;
 
@@ -49,25 +51,141 @@ impl<T: Clone> BoundClonedExt for std::ops::Bound<&T> {
     }
 }
 
-pub trait OrdSize {
+/// The measure of a run of elements in an `OrdBTree`.
+///
+/// Summaries are combined with `+`, which has to be associative with `Default::default()`
+/// as its identity. It doesn't have to be invertible (a maximum is fine), since nodes
+/// recalculate their summaries from their contents when elements leave them.
+pub trait OrdSummary: Copy + Default + fmt::Debug + Add<Output = Self> {
+    /// The primary dimension, which `get`, `insert` and `remove` use as the position.
     fn size(&self) -> usize;
 }
 
+impl OrdSummary for usize {
+    #[inline(always)]
+    fn size(&self) -> usize {
+        *self
+    }
+}
+
+/// A coordinate system over summaries of type `S`, used by `OrdBTree::seek_by`.
+pub trait OrdDimension<S> {
+    fn measure(&self, summary: &S) -> usize;
+}
+
+impl<S, F: Fn(&S) -> usize> OrdDimension<S> for F {
+    #[inline(always)]
+    fn measure(&self, summary: &S) -> usize {
+        self(summary)
+    }
+}
+
+pub trait OrdSize {
+    type Summary: OrdSummary;
+
+    fn summary(&self) -> Self::Summary;
+
+    #[inline(always)]
+    fn size(&self) -> usize {
+        self.summary().size()
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct OrdSizeOne;
 
 impl OrdSize for OrdSizeOne {
+    type Summary = usize;
+
     #[inline(always)]
-    fn size(&self) -> usize {
+    fn summary(&self) -> usize {
         1
     }
 }
 
+/// The length of a piece of text in every coordinate system an editor needs.
+///
+/// The primary dimension (`size`) is `bytes`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct TextSummary {
+    pub bytes: usize,
+    pub chars: usize,
+    pub newlines: usize,
+    pub utf16: usize,
+}
+
+impl From<&str> for TextSummary {
+    fn from(s: &str) -> Self {
+        let mut summary = Self {
+            bytes: s.len(),
+            ..Self::default()
+        };
+        for c in s.chars() {
+            summary.chars += 1;
+            summary.utf16 += c.len_utf16();
+            if c == '\n' {
+                summary.newlines += 1;
+            }
+        }
+        summary
+    }
+}
+
+impl Add for TextSummary {
+    type Output = Self;
+
+    #[inline]
+    fn add(self, rhs: Self) -> Self {
+        Self {
+            bytes: self.bytes + rhs.bytes,
+            chars: self.chars + rhs.chars,
+            newlines: self.newlines + rhs.newlines,
+            utf16: self.utf16 + rhs.utf16,
+        }
+    }
+}
+
+impl OrdSummary for TextSummary {
+    #[inline(always)]
+    fn size(&self) -> usize {
+        self.bytes
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TextDimension {
+    Bytes,
+    Chars,
+    Newlines,
+    Utf16,
+}
+
+impl OrdDimension<TextSummary> for TextDimension {
+    #[inline]
+    fn measure(&self, summary: &TextSummary) -> usize {
+        match self {
+            Self::Bytes => summary.bytes,
+            Self::Chars => summary.chars,
+            Self::Newlines => summary.newlines,
+            Self::Utf16 => summary.utf16,
+        }
+    }
+}
+
+impl OrdSize for String {
+    type Summary = TextSummary;
+
+    #[inline]
+    fn summary(&self) -> TextSummary {
+        self.as_str().into()
+    }
+}
+
 struct NodeElements<K: OrdSize, V> {
     _keys: OuterLenStackVec<K, MAX_NUM_ELEMENTS>,
     _values: OuterLenStackVec<V, MAX_NUM_ELEMENTS>,
     _len: u8,
-    size: usize,
+    summary: K::Summary,
     // parent: *mut Node<T>,
 }
 
@@ -76,7 +194,14 @@ impl<K: OrdSize, V> NodeElements<K, V> {
     //     unsafe { Self::from_raw_parts(OuterLenStackVec::new(), OuterLenStackVec::new(), 0, parent) }
     // }
     pub fn new() -> Self {
-        unsafe { Self::from_raw_parts(OuterLenStackVec::new(), OuterLenStackVec::new(), 0, 0) }
+        unsafe {
+            Self::from_raw_parts(
+                OuterLenStackVec::new(),
+                OuterLenStackVec::new(),
+                0,
+                K::Summary::default(),
+            )
+        }
     }
 
     #[inline]
@@ -113,7 +238,12 @@ impl<K: OrdSize, V> NodeElements<K, V> {
 
     #[inline(always)]
     pub fn size(&self) -> usize {
-        self.size
+        self.summary.size()
+    }
+
+    #[inline(always)]
+    pub fn summary(&self) -> K::Summary {
+        self.summary
     }
 
     #[inline(always)]
@@ -123,23 +253,26 @@ impl<K: OrdSize, V> NodeElements<K, V> {
     }
 
     #[inline(always)]
-    pub unsafe fn set_size(&mut self, size: usize) {
-        self.size = size;
+    pub unsafe fn set_summary(&mut self, summary: K::Summary) {
+        self.summary = summary;
     }
 
     #[must_use]
     #[inline]
     pub fn push(&mut self, key: K, value: V) -> Option<(K, V)> {
         unsafe {
-            self.size += key.size();
+            let key_summary = key.summary();
 
             let overflow_key = self._keys.push(&mut self.len(), key);
             let mut len = self.len();
             let overflow_value = self._values.push(&mut len, value);
             self.set_len(len);
 
-            if let Some(overflow_key) = &overflow_value {
-                self.size -= overflow_key.size();
+            // Summaries can't be subtracted, so the overflowing key is left out by recalculating.
+            if overflow_key.is_some() {
+                self.recalc_summary();
+            } else {
+                self.summary = self.summary + key_summary;
             }
 
             match (overflow_key, overflow_value) {
@@ -153,15 +286,18 @@ impl<K: OrdSize, V> NodeElements<K, V> {
     #[must_use]
     pub fn insert(&mut self, idx: usize, key: K, value: V) -> Option<(K, V)> {
         unsafe {
-            self.size += key.size();
+            let key_summary = key.summary();
 
             let overflow_key = self._keys.insert(&mut self.len(), idx, key);
             let mut len = self.len();
             let overflow_value = self._values.insert(&mut len, idx, value);
             self.set_len(len);
 
-            if let Some(overflow_key) = &overflow_key {
-                self.size -= overflow_key.size();
+            // Summaries can't be subtracted, so the overflowing key is left out by recalculating.
+            if overflow_key.is_some() {
+                self.recalc_summary();
+            } else {
+                self.summary = self.summary + key_summary;
             }
 
             match (overflow_key, overflow_value) {
@@ -180,11 +316,14 @@ impl<K: OrdSize, V> NodeElements<K, V> {
             let popped_value = self._values.pop(&mut len);
             self.set_len(len);
 
-            if let Some(popped_key) = &popped_key {
-                self.size -= popped_key.size();
+            match (popped_key, popped_value) {
+                (Some(popped_key), Some(popped_value)) => {
+                    self.recalc_summary();
+                    Some((popped_key, popped_value))
+                }
+                (None, None) => None,
+                _ => unreachable!(),
             }
-
-            popped_key
         }
     }
 
@@ -195,7 +334,7 @@ impl<K: OrdSize, V> NodeElements<K, V> {
             let removed_value = self._values.remove(&mut len, idx);
             self.set_len(len);
 
-            self.size -= removed_key.size();
+            self.recalc_summary();
 
             (removed_key, removed_value)
         }
@@ -208,7 +347,7 @@ impl<K: OrdSize, V> NodeElements<K, V> {
         OuterLenStackVec<K, MAX_NUM_ELEMENTS>,
         OuterLenStackVec<V, MAX_NUM_ELEMENTS>,
         usize,
-        usize,
+        K::Summary,
     ) {
         unsafe {
             let mb = mem::ManuallyDrop::new(self);
@@ -216,7 +355,7 @@ impl<K: OrdSize, V> NodeElements<K, V> {
                 ptr::read(&mb._keys),
                 ptr::read(&mb._values),
                 mb._len as _,
-                mb.size,
+                mb.summary,
             )
         }
     }
@@ -226,13 +365,13 @@ impl<K: OrdSize, V> NodeElements<K, V> {
         keys: OuterLenStackVec<K, MAX_NUM_ELEMENTS>,
         values: OuterLenStackVec<V, MAX_NUM_ELEMENTS>,
         len: usize,
-        size: usize,
+        summary: K::Summary,
     ) -> Self {
         Self {
             _keys: keys,
             _values: values,
             _len: len as _,
-            size,
+            summary,
         }
     }
 
@@ -273,8 +412,11 @@ impl<K: OrdSize, V> NodeElements<K, V> {
     // }
 
     #[inline]
-    fn recalc_size(&mut self) {
-        self.size = self.keys().iter().map(|key| key.size()).sum();
+    fn recalc_summary(&mut self) {
+        self.summary = self
+            .keys()
+            .iter()
+            .fold(K::Summary::default(), |acc, key| acc + key.summary());
     }
 
     #[inline]
@@ -284,17 +426,21 @@ impl<K: OrdSize, V> NodeElements<K, V> {
 
         unsafe {
             ptr::copy_nonoverlapping(
-                self._elements.as_ptr().add(B + 1),
-                right._elements.as_mut_ptr(),
+                self._keys.as_ptr().add(B + 1),
+                right._keys.as_mut_ptr(),
+                B - 2,
+            );
+            ptr::copy_nonoverlapping(
+                self._values.as_ptr().add(B + 1),
+                right._values.as_mut_ptr(),
                 B - 2,
             );
             right.set_len(B - 2);
 
-            right.recalc_size();
-            self.size -= right.size();
-
+            right.recalc_summary();
             right.push(overflow_key, overflow_value).assert_none();
 
+            // `pop` recalculates the summary of the remaining elements.
             self.set_len(B + 1);
             self.pop().unwrap()
         }
@@ -306,14 +452,55 @@ impl<K: OrdSize, V> NodeElements<K, V> {
         unsafe {
             self.push(sep_key, sep_value).assert_none();
             ptr::copy_nonoverlapping(
-                right._elements.as_ptr(),
-                self._elements.as_mut_ptr().add(self.len()),
+                right._keys.as_ptr(),
+                self._keys.as_mut_ptr().add(self.len()),
+                right.len(),
+            );
+            ptr::copy_nonoverlapping(
+                right._values.as_ptr(),
+                self._values.as_mut_ptr().add(self.len()),
                 right.len(),
             );
             self.set_len(self.len() + right.len());
             right.set_len(0);
 
-            self.size += right.size;
+            self.summary = self.summary + right.summary;
+        }
+    }
+
+    /// Moves the elements `at..` into a new `NodeElements`.
+    /// Both summaries are recalculated from the keys.
+    pub fn split_off(&mut self, at: usize) -> Self {
+        unsafe {
+            let mut len = self.len();
+            let right_keys = self._keys.split_at(&mut len.clone(), at);
+            let right_values = self._values.split_at(&mut len, at);
+            self.set_len(len);
+
+            let (right_keys, right_len) = right_keys.into_raw_parts();
+            let mut right = Self::from_raw_parts(
+                right_keys,
+                right_values.into_raw_parts().0,
+                right_len,
+                K::Summary::default(),
+            );
+
+            self.recalc_summary();
+            right.recalc_summary();
+
+            right
+        }
+    }
+
+    pub fn into_pairs(
+        self,
+    ) -> std::iter::Zip<StackVecIntoIter<K, MAX_NUM_ELEMENTS>, StackVecIntoIter<V, MAX_NUM_ELEMENTS>>
+    {
+        unsafe {
+            let (keys, values, len, _) = self.into_raw_parts();
+            StackVec::from_raw_parts(keys, len)
+                .into_iter()
+                .zip(StackVec::from_raw_parts(values, len))
         }
     }
 }
@@ -332,7 +519,7 @@ impl<K: OrdSize + Clone, V: Clone> Clone for NodeElements<K, V> {
                 self._keys.clone(self.len()).into_raw_parts().0,
                 self._values.clone(self.len()).into_raw_parts().0,
                 self.len(),
-                self.size(),
+                self.summary(),
                 // self.parent,
             )
         }
@@ -343,15 +530,16 @@ impl<K: OrdSize + fmt::Debug, V: fmt::Debug> fmt::Debug for NodeElements<K, V> {
     #[inline(always)]
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("NodeData")
-            .field("elements", &self.elements())
-            .field("size", &self.size())
+            .field("keys", &self.keys())
+            .field("values", &self.values())
+            .field("summary", &self.summary())
             .finish()
     }
 }
 
 impl<K: OrdSize, V> Drop for NodeElements<K, V> {
     fn drop(&mut self) {
-        while let Some(_) = self.pop() {}
+        while self.pop().is_some() {}
     }
 }
 
@@ -433,6 +621,13 @@ impl<K: OrdSize, V> Child<K, V> {
         }
     }
 
+    pub fn summary(&self) -> K::Summary {
+        match self {
+            Self::Node(node) => node.summary(),
+            Self::Leaf(leaf) => leaf.summary(),
+        }
+    }
+
     // pub fn parent(&self) -> *const Node<T> {
     //     match self {
     //         Self::Node(node) => node.parent(),
@@ -447,14 +642,14 @@ impl<K: OrdSize, V> Child<K, V> {
     //     }
     // }
 
-    pub fn as_ref(&self) -> ChildRef<K, V> {
+    pub fn as_ref(&self) -> ChildRef<'_, K, V> {
         match self {
             Self::Node(node) => ChildRef::Node(node),
             Self::Leaf(leaf) => ChildRef::Leaf(leaf),
         }
     }
 
-    pub fn as_mut(&mut self) -> ChildRefMut<K, V> {
+    pub fn as_mut(&mut self) -> ChildRefMut<'_, K, V> {
         match self {
             Self::Node(node) => ChildRefMut::Node(node),
             Self::Leaf(leaf) => ChildRefMut::Leaf(leaf),
@@ -475,14 +670,14 @@ impl<K: OrdSize, V> Child<K, V> {
         }
     }
 
-    pub fn try_as_node_ref(&self) -> Option<&Box<Node<K, V>>> {
+    pub fn try_as_node_ref(&self) -> Option<&Node<K, V>> {
         match self {
             Self::Node(node) => Some(node),
             Self::Leaf(_) => None,
         }
     }
 
-    pub fn try_as_leaf_ref(&self) -> Option<&Box<NodeElements<K, V>>> {
+    pub fn try_as_leaf_ref(&self) -> Option<&NodeElements<K, V>> {
         match self {
             Self::Leaf(leaf) => Some(leaf),
             Self::Node(_) => None,
@@ -550,6 +745,308 @@ impl<K: OrdSize, V> Child<K, V> {
             Self::Leaf(_) => false,
         }
     }
+
+    /// Builds a tree of height `height` from `elements` and `children`, or returns the only child
+    /// (with height `height - 1`) if there are no elements.
+    fn from_parts(
+        elements: NodeElements<K, V>,
+        mut children: Children<K, V>,
+        height: usize,
+    ) -> (Self, usize) {
+        if elements.len() == 0 {
+            (children.pop().unwrap(), height - 1)
+        } else {
+            (Self::Node(Node::from_separated(elements, children)), height)
+        }
+    }
+
+    /// Appends the elements of the tree to `out` in order.
+    fn append_to(self, out: &mut Vec<(K, V)>) {
+        match self {
+            Self::Node(node) => {
+                let (elements, children) = node.separate();
+                let mut children = children.into_iter();
+                for pair in elements.into_pairs() {
+                    children.next().unwrap().append_to(out);
+                    out.push(pair);
+                }
+                children.next().unwrap().append_to(out);
+            }
+            Self::Leaf(leaf) => out.extend(leaf.into_pairs()),
+        }
+    }
+
+    /// Builds a tree of height `height` out of the next `len` pairs of `pairs`,
+    /// with the elements spread evenly between the children.
+    fn build<I: Iterator<Item = (K, V)>>(pairs: &mut I, len: usize, height: usize) -> Self {
+        if height == 0 {
+            let mut leaf = Box::new(NodeElements::new());
+            for (key, value) in pairs.take(len) {
+                leaf.push(key, value).assert_none();
+            }
+            return Self::Leaf(leaf);
+        }
+
+        // Each child takes its elements plus one out of `len + 1`. A non-root child of height
+        // `height - 1` takes between `MIN_NUM_CHILDREN.pow(height)` and `MAX_NUM_CHILDREN.pow(height)`,
+        // and a root needs at least 2 children.
+        let max_child_weight = MAX_NUM_CHILDREN.pow(height as u32);
+        let min_child_weight = MIN_NUM_CHILDREN.pow(height as u32);
+        let num_children = ((len + max_child_weight) / max_child_weight)
+            .max(MIN_NUM_CHILDREN.min((len + 1) / min_child_weight));
+
+        let mut node: Option<Box<Node<K, V>>> = None;
+        let mut sep = None;
+        for i in 0..num_children {
+            let child_len =
+                (len + 1) / num_children + usize::from(i < (len + 1) % num_children) - 1;
+            let child = Self::build(pairs, child_len, height - 1);
+
+            match (&mut node, sep.take()) {
+                (Some(node), Some((key, value))) => node.push(key, value, child).assert_none(),
+                (None, None) => node = Some(Node::new(child)),
+                _ => unreachable!(),
+            }
+            if i + 1 < num_children {
+                sep = pairs.next();
+            }
+        }
+
+        Self::Node(node.unwrap())
+    }
+
+    /// Builds a balanced tree out of `pairs`, and returns it with its height.
+    ///
+    /// O(n)
+    fn from_pairs<I: ExactSizeIterator<Item = (K, V)>>(mut pairs: I) -> (Self, usize) {
+        let len = pairs.len();
+
+        let mut height = 0;
+        while MAX_NUM_CHILDREN.pow(height as u32 + 1) - 1 < len {
+            height += 1;
+        }
+
+        (Self::build(&mut pairs, len, height), height)
+    }
+
+    /// Joins the tree `self` of height `height`, the elements `pairs` and the tree `right` of
+    /// height `right_height`, in that order.
+    ///
+    /// O(log n + k)
+    fn join_pairs(
+        self,
+        height: usize,
+        pairs: Vec<(K, V)>,
+        right: Self,
+        right_height: usize,
+    ) -> (Self, usize) {
+        let mut pairs = pairs.into_iter();
+        match (pairs.next(), pairs.next_back()) {
+            (None, _) => self.concat(height, right, right_height),
+            (Some((key, value)), None) => self.join(height, key, value, right, right_height),
+            (Some((first_key, first_value)), Some((last_key, last_value))) => {
+                let (mid, mid_height) = Self::from_pairs(pairs);
+                let (left, left_height) =
+                    self.join(height, first_key, first_value, mid, mid_height);
+                left.join(left_height, last_key, last_value, right, right_height)
+            }
+        }
+    }
+
+    /// Joins two siblings of the same height separated by `key`.
+    /// Either merges them into a single child, or balances them such that both have at least
+    /// `MIN_NUM_ELEMENTS` elements and returns the separator and the right sibling.
+    fn join_siblings(self, key: K, value: V, right: Self) -> (Self, Option<(K, V, Self)>) {
+        let (mut sep_key, mut sep_value) = (key, value);
+
+        match (self, right) {
+            (Self::Node(mut left), Self::Node(mut right)) => {
+                if left.num_elements() + right.num_elements() < MAX_NUM_ELEMENTS {
+                    left.merge(sep_key, sep_value, right);
+                    return (Self::Node(left), None);
+                }
+
+                let left_len = (left.num_elements() + right.num_elements()) / 2;
+                while left_len < left.num_elements() {
+                    let (key, value, child) = left.pop().unwrap();
+                    let key = mem::replace(&mut sep_key, key);
+                    let value = mem::replace(&mut sep_value, value);
+                    right.push_front_or_split(key, value, child).assert_none();
+                }
+                while left.num_elements() < left_len {
+                    let (key, value, child) = right.pop_front();
+                    let key = mem::replace(&mut sep_key, key);
+                    let value = mem::replace(&mut sep_value, value);
+                    left.push(key, value, child).assert_none();
+                }
+
+                (
+                    Self::Node(left),
+                    Some((sep_key, sep_value, Self::Node(right))),
+                )
+            }
+            (Self::Leaf(mut left), Self::Leaf(mut right)) => {
+                if left.len() + right.len() < MAX_NUM_ELEMENTS {
+                    left.merge(sep_key, sep_value, right.as_mut());
+                    return (Self::Leaf(left), None);
+                }
+
+                let left_len = (left.len() + right.len()) / 2;
+                while left_len < left.len() {
+                    let (key, value) = left.pop().unwrap();
+                    let key = mem::replace(&mut sep_key, key);
+                    let value = mem::replace(&mut sep_value, value);
+                    right.insert(0, key, value).assert_none();
+                }
+                while left.len() < left_len {
+                    let (key, value) = right.remove(0);
+                    let key = mem::replace(&mut sep_key, key);
+                    let value = mem::replace(&mut sep_value, value);
+                    left.push(key, value).assert_none();
+                }
+
+                (
+                    Self::Leaf(left),
+                    Some((sep_key, sep_value, Self::Leaf(right))),
+                )
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    /// Joins the tree `self` of height `height` and the tree `right` of height `right_height`
+    /// with `key` in the middle. Returns the joined tree and its height.
+    ///
+    /// O(|height - right_height| + 1)
+    fn join(
+        self,
+        height: usize,
+        key: K,
+        value: V,
+        right: Self,
+        right_height: usize,
+    ) -> (Self, usize) {
+        let (root, overflow, height) = match height.cmp(&right_height) {
+            Ordering::Equal => {
+                let (root, overflow) = self.join_siblings(key, value, right);
+                (root, overflow, height)
+            }
+            Ordering::Greater => {
+                let mut root = self.try_into_node().unwrap();
+                let overflow = root.join_right(height, key, value, right, right_height);
+                (Self::Node(root), overflow, height)
+            }
+            Ordering::Less => {
+                let mut root = right.try_into_node().unwrap();
+                let overflow = root.join_left(right_height, self, height, key, value);
+                (Self::Node(root), overflow, right_height)
+            }
+        };
+
+        match overflow {
+            Some((key, value, right)) => {
+                let mut root = Node::new(root);
+                root.push(key, value, right).assert_none();
+                (Self::Node(root), height + 1)
+            }
+            None => (root, height),
+        }
+    }
+
+    /// Concatenates the tree `self` of height `height` and the tree `right` of height `right_height`.
+    ///
+    /// O(log n)
+    fn concat(self, height: usize, right: Self, right_height: usize) -> (Self, usize) {
+        if right.num_elements() == 0 {
+            return (self, height);
+        } else if self.num_elements() == 0 {
+            return (right, right_height);
+        }
+
+        let ((first, _), (right, right_height)) = right.split(right_height, 1, true);
+        let (key, value) = first.try_into_leaf().unwrap().pop().unwrap();
+        self.join(height, key, value, right, right_height)
+    }
+
+    /// Splits the tree `self` of height `height` at `pos` into two trees and returns them with
+    /// their heights. The element that straddles `pos` goes to the left tree iff `straddling_left`.
+    ///
+    /// O(log n)
+    fn split(
+        self,
+        height: usize,
+        pos: usize,
+        straddling_left: bool,
+    ) -> ((Self, usize), (Self, usize)) {
+        let goes_right = |start: usize, end: usize| {
+            if straddling_left {
+                pos <= start
+            } else {
+                pos < end
+            }
+        };
+
+        match self {
+            Self::Node(node) => {
+                let mut partial_sum = 0;
+                let mut idx = node.num_elements();
+                let children = node.children();
+                for (i, key) in node.keys().iter().enumerate() {
+                    let start = partial_sum + children.get(i).unwrap().size();
+                    let end = start + key.size();
+                    if goes_right(start, end) {
+                        idx = i;
+                        break;
+                    }
+                    partial_sum = end;
+                }
+
+                let (mut left_elements, mut left_children) = node.separate();
+                let right_children = left_children.split_at(idx + 1);
+                let mid_child = left_children.pop().unwrap();
+                let mut right_elements = left_elements.split_off(idx);
+                let left_sep = left_elements.pop();
+                let right_sep = (0 < right_elements.len()).then(|| right_elements.remove(0));
+
+                let ((mid_left, mid_left_height), (mid_right, mid_right_height)) =
+                    mid_child.split(height - 1, pos.saturating_sub(partial_sum), straddling_left);
+
+                let left = match left_sep {
+                    Some((key, value)) => {
+                        let (left, left_height) =
+                            Self::from_parts(left_elements, left_children, height);
+                        left.join(left_height, key, value, mid_left, mid_left_height)
+                    }
+                    None => (mid_left, mid_left_height),
+                };
+                let right = match right_sep {
+                    Some((key, value)) => {
+                        let (right, right_height) =
+                            Self::from_parts(right_elements, right_children, height);
+                        mid_right.join(mid_right_height, key, value, right, right_height)
+                    }
+                    None => (mid_right, mid_right_height),
+                };
+
+                (left, right)
+            }
+            Self::Leaf(mut leaf) => {
+                let mut partial_sum = 0;
+                let mut idx = leaf.len();
+                for (i, key) in leaf.keys().iter().enumerate() {
+                    let end = partial_sum + key.size();
+                    if goes_right(partial_sum, end) {
+                        idx = i;
+                        break;
+                    }
+                    partial_sum = end;
+                }
+
+                let right = leaf.split_off(idx);
+                ((Self::Leaf(leaf), 0), (Self::Leaf(Box::new(right)), 0))
+            }
+        }
+    }
 }
 
 impl<'a, K: OrdSize, V> ChildRef<'a, K, V> {
@@ -567,6 +1064,13 @@ impl<'a, K: OrdSize, V> ChildRef<'a, K, V> {
         }
     }
 
+    pub fn summary(&self) -> K::Summary {
+        match self {
+            Self::Node(node) => node.summary(),
+            Self::Leaf(leaf) => leaf.summary(),
+        }
+    }
+
     // pub fn parent(&self) -> *const Node<T> {
     //     match self {
     //         Self::Node(node) => node.parent(),
@@ -626,6 +1130,13 @@ impl<'a, K: OrdSize, V> ChildRefMut<'a, K, V> {
         }
     }
 
+    pub fn summary(&self) -> K::Summary {
+        match self {
+            Self::Node(node) => node.summary(),
+            Self::Leaf(leaf) => leaf.summary(),
+        }
+    }
+
     // pub fn parent(&self) -> *const Node<T> {
     //     match self {
     //         Self::Node(node) => node.parent(),
@@ -640,21 +1151,21 @@ impl<'a, K: OrdSize, V> ChildRefMut<'a, K, V> {
     //     }
     // }
 
-    pub fn as_ref(self) -> ChildRef<'a, K, V> {
+    pub fn into_ref(self) -> ChildRef<'a, K, V> {
         match self {
             Self::Node(node) => ChildRef::Node(node),
             Self::Leaf(leaf) => ChildRef::Leaf(leaf),
         }
     }
 
-    pub fn borrow(&self) -> ChildRef<K, V> {
+    pub fn borrow(&self) -> ChildRef<'_, K, V> {
         match self {
             Self::Node(node) => ChildRef::Node(node),
             Self::Leaf(leaf) => ChildRef::Leaf(leaf),
         }
     }
 
-    pub fn borrow_mut(&mut self) -> ChildRefMut<K, V> {
+    pub fn borrow_mut(&mut self) -> ChildRefMut<'_, K, V> {
         match self {
             Self::Node(node) => ChildRefMut::Node(node),
             Self::Leaf(leaf) => ChildRefMut::Leaf(leaf),
@@ -770,21 +1281,21 @@ impl<'a, K: OrdSize, V> IntoIterator for ChildrenSlice<'a, K, V> {
 
     fn into_iter(self) -> Self::IntoIter {
         match self {
-            Self::Nodes(nodes) => ChildrenIter::Nodes(nodes.into_iter()),
-            Self::Leafs(leafs) => ChildrenIter::Leafs(leafs.into_iter()),
+            Self::Nodes(nodes) => ChildrenIter::Nodes(nodes.iter()),
+            Self::Leafs(leafs) => ChildrenIter::Leafs(leafs.iter()),
         }
     }
 }
 
 impl<'a, K: OrdSize, V> ChildrenSliceMut<'a, K, V> {
-    pub fn get(&self, i: usize) -> Option<ChildRef<K, V>> {
+    pub fn get(&self, i: usize) -> Option<ChildRef<'_, K, V>> {
         match self {
             Self::Nodes(nodes) => Some(ChildRef::Node(nodes.get(i)?)),
             Self::Leafs(leafs) => Some(ChildRef::Leaf(leafs.get(i)?)),
         }
     }
 
-    pub fn get_mut(&mut self, i: usize) -> Option<ChildRefMut<K, V>> {
+    pub fn get_mut(&mut self, i: usize) -> Option<ChildRefMut<'_, K, V>> {
         match self {
             Self::Nodes(nodes) => Some(ChildRefMut::Node(nodes.get_mut(i)?)),
             Self::Leafs(leafs) => Some(ChildRefMut::Leaf(leafs.get_mut(i)?)),
@@ -805,7 +1316,10 @@ impl<'a, K: OrdSize, V> ChildrenSliceMut<'a, K, V> {
         }
     }
 
-    pub fn slice<B: std::ops::RangeBounds<usize>>(&self, bounds: B) -> Option<ChildrenSlice<K, V>> {
+    pub fn slice<B: std::ops::RangeBounds<usize>>(
+        &self,
+        bounds: B,
+    ) -> Option<ChildrenSlice<'_, K, V>> {
         let bounds = (
             BoundClonedExt::cloned(&bounds.start_bound()),
             BoundClonedExt::cloned(&bounds.end_bound()),
@@ -820,7 +1334,7 @@ impl<'a, K: OrdSize, V> ChildrenSliceMut<'a, K, V> {
     pub fn slice_mut<B: std::ops::RangeBounds<usize>>(
         &mut self,
         bounds: B,
-    ) -> Option<ChildrenSliceMut<K, V>> {
+    ) -> Option<ChildrenSliceMut<'_, K, V>> {
         let bounds = (
             BoundClonedExt::cloned(&bounds.start_bound()),
             BoundClonedExt::cloned(&bounds.end_bound()),
@@ -858,14 +1372,14 @@ impl<'a, K: OrdSize, V> ChildrenSliceMut<'a, K, V> {
         }
     }
 
-    pub fn iter(&self) -> ChildrenIter<K, V> {
+    pub fn iter(&self) -> ChildrenIter<'_, K, V> {
         match self {
             Self::Nodes(nodes) => ChildrenIter::Nodes(nodes.iter()),
             Self::Leafs(leafs) => ChildrenIter::Leafs(leafs.iter()),
         }
     }
 
-    pub fn iter_mut(&mut self) -> ChildrenIterMut<K, V> {
+    pub fn iter_mut(&mut self) -> ChildrenIterMut<'_, K, V> {
         match self {
             Self::Nodes(nodes) => ChildrenIterMut::Nodes(nodes.iter_mut()),
             Self::Leafs(leafs) => ChildrenIterMut::Leafs(leafs.iter_mut()),
@@ -890,8 +1404,8 @@ impl<'a, K: OrdSize, V> IntoIterator for ChildrenSliceMut<'a, K, V> {
 
     fn into_iter(self) -> Self::IntoIter {
         match self {
-            Self::Nodes(nodes) => ChildrenIterMut::Nodes(nodes.into_iter()),
-            Self::Leafs(leafs) => ChildrenIterMut::Leafs(leafs.into_iter()),
+            Self::Nodes(nodes) => ChildrenIterMut::Nodes(nodes.iter_mut()),
+            Self::Leafs(leafs) => ChildrenIterMut::Leafs(leafs.iter_mut()),
         }
     }
 }
@@ -1106,7 +1620,7 @@ impl<K: OrdSize, V> OuterLenChildren<K, V> {
     }
 
     #[inline]
-    pub unsafe fn as_slice(&self, len: usize) -> ChildrenSlice<K, V> {
+    pub unsafe fn as_slice(&self, len: usize) -> ChildrenSlice<'_, K, V> {
         match self {
             Self::Nodes(nodes) => ChildrenSlice::Nodes(nodes.as_slice(len)),
             Self::Leafs(leafs) => ChildrenSlice::Leafs(leafs.as_slice(len)),
@@ -1114,7 +1628,7 @@ impl<K: OrdSize, V> OuterLenChildren<K, V> {
     }
 
     #[inline]
-    pub unsafe fn as_slice_mut(&mut self, len: usize) -> ChildrenSliceMut<K, V> {
+    pub unsafe fn as_slice_mut(&mut self, len: usize) -> ChildrenSliceMut<'_, K, V> {
         match self {
             Self::Nodes(nodes) => ChildrenSliceMut::Nodes(nodes.as_slice_mut(len)),
             Self::Leafs(leafs) => ChildrenSliceMut::Leafs(leafs.as_slice_mut(len)),
@@ -1224,7 +1738,7 @@ impl<K: OrdSize, V> Children<K, V> {
     }
 
     #[inline]
-    pub fn as_slice(&self) -> ChildrenSlice<K, V> {
+    pub fn as_slice(&self) -> ChildrenSlice<'_, K, V> {
         match &self._data {
             OuterLenChildren::Nodes(nodes) => {
                 ChildrenSlice::Nodes(unsafe { nodes.as_slice(self.len()) })
@@ -1236,7 +1750,7 @@ impl<K: OrdSize, V> Children<K, V> {
     }
 
     #[inline]
-    pub fn as_slice_mut(&mut self) -> ChildrenSliceMut<K, V> {
+    pub fn as_slice_mut(&mut self) -> ChildrenSliceMut<'_, K, V> {
         let len = self.len();
         match &mut self._data {
             OuterLenChildren::Nodes(nodes) => {
@@ -1286,7 +1800,7 @@ impl<K: OrdSize, V> IntoIterator for Children<K, V> {
 
 impl<K: OrdSize, V> Drop for Children<K, V> {
     fn drop(&mut self) {
-        while let Some(_) = self.pop() {}
+        while self.pop().is_some() {}
     }
 }
 
@@ -1302,11 +1816,11 @@ impl<K: OrdSize + fmt::Debug, V: fmt::Debug> fmt::Debug for Children<K, V> {
     }
 }
 
-impl<K: OrdSize, V> Into<ChildrenStackVec<K, V>> for Children<K, V> {
+impl<K: OrdSize, V> From<Children<K, V>> for ChildrenStackVec<K, V> {
     #[inline]
-    fn into(self) -> ChildrenStackVec<K, V> {
+    fn from(children: Children<K, V>) -> Self {
         unsafe {
-            let (data, len) = self.into_raw_parts();
+            let (data, len) = children.into_raw_parts();
 
             match data {
                 OuterLenChildren::Nodes(nodes) => {
@@ -1320,11 +1834,11 @@ impl<K: OrdSize, V> Into<ChildrenStackVec<K, V>> for Children<K, V> {
     }
 }
 
-impl<K: OrdSize, V> Into<Children<K, V>> for ChildrenStackVec<K, V> {
+impl<K: OrdSize, V> From<ChildrenStackVec<K, V>> for Children<K, V> {
     #[inline]
-    fn into(self) -> Children<K, V> {
+    fn from(children: ChildrenStackVec<K, V>) -> Self {
         unsafe {
-            match self {
+            match children {
                 ChildrenStackVec::Nodes(nodes) => {
                     let (data, len) = nodes.into_raw_parts();
                     Children::from_raw_parts(OuterLenChildren::Nodes(data), len)
@@ -1400,7 +1914,7 @@ impl<K: OrdSize, V> Node<K, V> {
                         OuterLenChildren::Nodes(OuterLenStackVec::new()),
                     ));
                     // *node.parent_mut() = slf.as_mut();
-                    slf.set_size(node.size());
+                    slf.set_summary(node.summary());
                     slf._children.push(&mut 0, Child::Node(node)).assert_none();
                     slf
                 }
@@ -1411,7 +1925,7 @@ impl<K: OrdSize, V> Node<K, V> {
                         OuterLenChildren::Leafs(OuterLenStackVec::new()),
                     ));
                     // leaf.parent = slf.as_mut();
-                    slf.set_size(leaf.size());
+                    slf.set_summary(leaf.summary());
                     slf._children.push(&mut 0, Child::Leaf(leaf)).assert_none();
                     slf
                 }
@@ -1431,26 +1945,26 @@ impl<K: OrdSize, V> Node<K, V> {
 
     #[inline]
     pub fn values(&self) -> &[V] {
-        self._elements.keys()
+        self._elements.values()
     }
 
     #[inline]
     pub fn values_mut(&mut self) -> &mut [V] {
-        self._elements.keys_mut()
+        self._elements.values_mut()
     }
 
     #[inline]
-    pub fn children(&self) -> ChildrenSlice<K, V> {
+    pub fn children(&self) -> ChildrenSlice<'_, K, V> {
         unsafe { self._children.as_slice(self.num_children()) }
     }
 
     #[inline]
-    pub fn children_mut(&mut self) -> ChildrenSliceMut<K, V> {
+    pub fn children_mut(&mut self) -> ChildrenSliceMut<'_, K, V> {
         unsafe { self._children.as_slice_mut(self.num_children()) }
     }
 
     #[inline]
-    pub fn get_all_mut(&mut self) -> (&mut [K], &mut [V], ChildrenSliceMut<K, V>) {
+    pub fn get_all_mut(&mut self) -> (&mut [K], &mut [V], ChildrenSliceMut<'_, K, V>) {
         let children = unsafe { self._children.as_slice_mut(self.num_children()) };
         let (keys, values) = self._elements.get_all_mut();
         (keys, values, children)
@@ -1464,12 +1978,12 @@ impl<K: OrdSize, V> Node<K, V> {
             let overflow_element = self._elements.push(key, value);
 
             // *child.parent_mut() = self;
-            self._elements.size += child.size();
+            self._elements.summary = self._elements.summary + child.summary();
             let overflow_child = self._children.push(&mut old_num_children, child);
 
             match (overflow_element, overflow_child) {
                 (Some((overflow_key, overflow_value)), Some(overflow_child)) => {
-                    self._elements.size -= overflow_child.size();
+                    self.recalc_summary();
 
                     Some((overflow_key, overflow_value, overflow_child))
                 }
@@ -1493,12 +2007,12 @@ impl<K: OrdSize, V> Node<K, V> {
             let overflow_element = self._elements.insert(idx, key, value);
 
             // *child.parent_mut() = self;
-            self._elements.size += child.size();
+            self._elements.summary = self._elements.summary + child.summary();
             let overflow_child = self._children.insert(&mut old_num_children, idx + 1, child);
 
             match (overflow_element, overflow_child) {
                 (Some((overflow_key, overflow_value)), Some(overflow_child)) => {
-                    self._elements.size -= overflow_child.size();
+                    self.recalc_summary();
 
                     Some((overflow_key, overflow_value, overflow_child))
                 }
@@ -1516,7 +2030,7 @@ impl<K: OrdSize, V> Node<K, V> {
 
             if let Some((popped_key, popped_value)) = popped_element {
                 let popped_child = self._children.pop(&mut old_num_children).unwrap();
-                self._elements.size -= popped_child.size();
+                self.recalc_summary();
 
                 Some((popped_key, popped_value, popped_child))
             } else {
@@ -1531,8 +2045,7 @@ impl<K: OrdSize, V> Node<K, V> {
 
             let (removed_key, removed_value) = self._elements.remove(idx);
             let removed_child = self._children.remove(&mut old_num_children, idx + 1);
-
-            self._elements.size -= removed_child.size();
+            self.recalc_summary();
 
             (removed_key, removed_value, removed_child)
         }
@@ -1594,7 +2107,7 @@ impl<K: OrdSize, V> Node<K, V> {
             },
         });
         unsafe {
-            let sep_element =
+            let (sep_key, sep_value) =
                 self._elements
                     .split(overflow_key, overflow_value, &mut right._elements);
 
@@ -1610,19 +2123,16 @@ impl<K: OrdSize, V> Node<K, V> {
                         B - 1,
                     );
 
-                    let mut right_children_size: usize = right_children
+                    let mut right_children_summary = right_children
                         .as_slice(B - 1)
                         .iter()
-                        .map(|child| child.size())
-                        .sum();
-                    self.set_size(self.size() - right_children_size);
-
-                    right_children_size += rightmost_child.size();
+                        .fold(K::Summary::default(), |acc, child| acc + child.summary());
+                    right_children_summary = right_children_summary + rightmost_child.summary();
                     right_children
                         .push(&mut (B - 1), rightmost_child)
                         .assert_none();
 
-                    right.set_size(right.size() + right_children_size);
+                    right.set_summary(right.summary() + right_children_summary);
                 }
                 (
                     OuterLenChildren::Leafs(self_children),
@@ -1635,27 +2145,28 @@ impl<K: OrdSize, V> Node<K, V> {
                         B - 1,
                     );
 
-                    let mut right_children_size: usize = right_children
+                    let mut right_children_summary = right_children
                         .as_slice(B - 1)
                         .iter()
-                        .map(|child| child.size())
-                        .sum();
-                    self.set_size(self.size() - right_children_size);
-
-                    right_children_size += rightmost_child.size();
+                        .fold(K::Summary::default(), |acc, child| acc + child.summary());
+                    right_children_summary = right_children_summary + rightmost_child.summary();
                     right_children
                         .push(&mut (B - 1), rightmost_child)
                         .assert_none();
 
-                    right.set_size(right.size() + right_children_size);
+                    right.set_summary(right.summary() + right_children_summary);
                 }
                 _ => unreachable!(),
             }
+            self.recalc_summary();
 
-            (sep_element, right)
+            (sep_key, sep_value, right)
         }
     }
 
+    // `right` is a detached child, which is already boxed, so taking it by value would move the
+    // whole node.
+    #[allow(clippy::boxed_local)]
     #[inline]
     pub fn merge(&mut self, sep_key: K, sep_value: V, mut right: Box<Self>) {
         assert!(self.num_elements() + right.num_elements() < MAX_NUM_ELEMENTS);
@@ -1716,6 +2227,135 @@ impl<K: OrdSize, V> Node<K, V> {
         }
     }
 
+    /// Recalculates the summary from the keys and the children.
+    fn recalc_summary(&mut self) {
+        self._elements.recalc_summary();
+        let summary = self
+            .children()
+            .iter()
+            .fold(self._elements.summary(), |acc, child| acc + child.summary());
+        self._elements.summary = summary;
+    }
+
+    /// The inverse of `separate`, the summary is recalculated from the keys and the children.
+    pub fn from_separated(mut elements: NodeElements<K, V>, children: Children<K, V>) -> Box<Self> {
+        assert_eq!(elements.len() + 1, children.len());
+
+        elements.recalc_summary();
+        let summary = children
+            .as_slice()
+            .iter()
+            .fold(elements.summary(), |acc, child| acc + child.summary());
+        unsafe {
+            elements.set_summary(summary);
+            Box::new(Self::from_raw_parts(elements, children.into_raw_parts().0))
+        }
+    }
+
+    /// Pushes an element and a child to the end, and splits `self` if it overflows.
+    /// Returns the separator and the new right sibling of `self` on a split.
+    #[must_use]
+    fn push_or_split(
+        &mut self,
+        key: K,
+        value: V,
+        child: Child<K, V>,
+    ) -> Option<(K, V, Child<K, V>)> {
+        let (overflow_key, overflow_value, overflow_child) = self.push(key, value, child)?;
+        let (sep_key, sep_value, right) = self.split(overflow_key, overflow_value, overflow_child);
+        Some((sep_key, sep_value, Child::Node(right)))
+    }
+
+    /// Inserts an element at `idx` and a child right after it, and splits `self` if it overflows.
+    #[must_use]
+    fn insert_or_split(
+        &mut self,
+        idx: usize,
+        key: K,
+        value: V,
+        child: Child<K, V>,
+    ) -> Option<(K, V, Child<K, V>)> {
+        let (overflow_key, overflow_value, overflow_child) = self.insert(idx, key, value, child)?;
+        let (sep_key, sep_value, right) = self.split(overflow_key, overflow_value, overflow_child);
+        Some((sep_key, sep_value, Child::Node(right)))
+    }
+
+    /// Pushes a child and an element to the front, and splits `self` if it overflows.
+    #[must_use]
+    fn push_front_or_split(
+        &mut self,
+        key: K,
+        value: V,
+        child: Child<K, V>,
+    ) -> Option<(K, V, Child<K, V>)> {
+        let overflow = self.insert(0, key, value, child);
+        self.children_mut().swap(0, 1);
+
+        let (overflow_key, overflow_value, overflow_child) = overflow?;
+        let (sep_key, sep_value, right) = self.split(overflow_key, overflow_value, overflow_child);
+        Some((sep_key, sep_value, Child::Node(right)))
+    }
+
+    /// Removes the first child and the first element.
+    fn pop_front(&mut self) -> (K, V, Child<K, V>) {
+        self.children_mut().swap(0, 1);
+        self.remove(0)
+    }
+
+    /// Joins the tree `right` of height `right_height` to the right of `self`,
+    /// where `self` has height `height` and `right_height < height`.
+    #[must_use]
+    fn join_right(
+        &mut self,
+        height: usize,
+        key: K,
+        value: V,
+        right: Child<K, V>,
+        right_height: usize,
+    ) -> Option<(K, V, Child<K, V>)> {
+        let (last_key, last_value, last_child) = self.pop().unwrap();
+
+        let (last_child, overflow) = if height - 1 == right_height {
+            last_child.join_siblings(key, value, right)
+        } else {
+            let mut last_node = last_child.try_into_node().unwrap();
+            let overflow = last_node.join_right(height - 1, key, value, right, right_height);
+            (Child::Node(last_node), overflow)
+        };
+
+        self.push(last_key, last_value, last_child).assert_none();
+        let (key, value, child) = overflow?;
+        self.push_or_split(key, value, child)
+    }
+
+    /// Joins the tree `left` of height `left_height` to the left of `self`,
+    /// where `self` has height `height` and `left_height < height`.
+    /// The overflow is the new right sibling of `self`.
+    #[must_use]
+    fn join_left(
+        &mut self,
+        height: usize,
+        left: Child<K, V>,
+        left_height: usize,
+        key: K,
+        value: V,
+    ) -> Option<(K, V, Child<K, V>)> {
+        let (first_key, first_value, first_child) = self.pop_front();
+
+        let (first_child, overflow) = if height - 1 == left_height {
+            left.join_siblings(key, value, first_child)
+        } else {
+            let mut first_node = first_child.try_into_node().unwrap();
+            let overflow = first_node.join_left(height - 1, left, left_height, key, value);
+            (Child::Node(first_node), overflow)
+        };
+
+        self.push_front_or_split(first_key, first_value, first_child)
+            .assert_none();
+        let (key, value, child) = overflow?;
+        self.insert_or_split(0, key, value, child)
+    }
+
     #[inline(always)]
     pub fn num_children(&self) -> usize {
         self.num_elements() + 1
@@ -1730,6 +2370,11 @@ impl<K: OrdSize, V> Node<K, V> {
         self._elements.size()
     }
 
+    #[inline(always)]
+    pub fn summary(&self) -> K::Summary {
+        self._elements.summary()
+    }
+
     // #[inline(always)]
     // pub fn parent(&self) -> *const Self {
     //     self._elements.parent
@@ -1746,12 +2391,12 @@ impl<K: OrdSize, V> Node<K, V> {
     }
 
     #[inline(always)]
-    pub unsafe fn set_size(&mut self, size: usize) {
-        self._elements.set_size(size);
+    pub unsafe fn set_summary(&mut self, summary: K::Summary) {
+        self._elements.set_summary(summary);
     }
 }
 
-impl<K: OrdSize + Clone, V> Clone for Node<K, V> {
+impl<K: OrdSize + Clone, V: Clone> Clone for Node<K, V> {
     fn clone(&self) -> Self {
         unsafe {
             Self::from_raw_parts(
@@ -1762,21 +2407,22 @@ impl<K: OrdSize + Clone, V> Clone for Node<K, V> {
     }
 }
 
-impl<K: OrdSize + fmt::Debug, V> fmt::Debug for Node<K, V> {
+impl<K: OrdSize + fmt::Debug, V: fmt::Debug> fmt::Debug for Node<K, V> {
     #[inline(always)]
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("Node")
             // .field("elements", &self._elements)
-            .field("elements", &self.elements())
+            .field("keys", &self.keys())
+            .field("values", &self.values())
             .field("children", &self.children())
-            .field("size", &self.size())
+            .field("summary", &self.summary())
             .finish()
     }
 }
 
 impl<K: OrdSize, V> Drop for Node<K, V> {
     fn drop(&mut self) {
-        while let Some(_) = self.pop() {}
+        while self.pop().is_some() {}
         unsafe {
             self._children.pop(&mut 1).unwrap();
         }
@@ -1995,7 +2641,7 @@ impl<K: OrdSize, V> OrdBTree<K, V> {
     pub fn new() -> Self {
         Self {
             // root: Child::Leaf(Box::new(NodeElements::new(ptr::null_mut()))),
-            root: Child::Leaf(Box::new(NodeElements::new())),
+            root: Child::Leaf(Box::default()),
             len: 0,
             depth: 1,
         }
@@ -2006,87 +2652,74 @@ impl<K: OrdSize, V> OrdBTree<K, V> {
         self.root.size()
     }
 
+    #[inline]
+    pub fn summary(&self) -> K::Summary {
+        self.root.summary()
+    }
+
     #[inline]
     pub fn len(&self) -> usize {
         self.len
     }
 
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
     #[inline]
     pub fn depth(&self) -> usize {
         self.depth
     }
 
-    pub fn get(&self, key: usize) -> Option<(usize, &K, &V)> {
+    pub fn get(&self, pos: usize) -> Option<(usize, &K, &V)> {
+        let (start, key, value) = self.seek_by(|summary: &K::Summary| summary.size(), pos)?;
+        Some((start.size(), key, value))
+    }
+
+    /// Finds the element that covers `value` in the coordinate system `dim`, and returns it
+    /// together with the summary of all the elements before it.
+    /// Elements that measure zero in `dim` are skipped.
+    ///
+    /// O(log n)
+    pub fn seek_by<D: OrdDimension<K::Summary>>(
+        &self,
+        dim: D,
+        value: usize,
+    ) -> Option<(K::Summary, &K, &V)> {
         let mut child = self.root.as_ref();
-        let mut partial_sum = 0;
+        let mut partial_sum = K::Summary::default();
 
-        'get_loop: loop {
+        'seek_loop: loop {
             match child {
-                ChildRef::Node(node) => match node.children() {
-                    ChildrenSlice::Nodes(children) => {
-                        for (elem, iter_child) in node.elements().iter().zip(children.iter()) {
-                            let iter_child = iter_child.as_ref();
-
-                            let child_size = iter_child.size();
-                            partial_sum += child_size;
-                            if key < partial_sum {
-                                partial_sum -= child_size;
-                                child = ChildRef::Node(iter_child);
-                                continue 'get_loop;
-                            }
+                ChildRef::Node(node) => {
+                    let children = node.children();
+                    for (i, (key, elem_value)) in node.keys().iter().zip(node.values()).enumerate()
+                    {
+                        let iter_child = children.get(i).unwrap();
 
-                            let elem_size = elem.size();
-                            partial_sum += elem_size;
-                            if key < partial_sum {
-                                return Some((partial_sum - elem_size, elem));
-                            }
+                        let child_end = partial_sum + iter_child.summary();
+                        if value < dim.measure(&child_end) {
+                            child = iter_child;
+                            continue 'seek_loop;
                         }
-                        child = ChildRef::Node(children.last().unwrap());
-                    }
-                    ChildrenSlice::Leafs(children) => {
-                        for (elem, iter_child) in node.elements().iter().zip(children.iter()) {
-                            let iter_child = iter_child.as_ref();
+                        partial_sum = child_end;
 
-                            let child_size = iter_child.size();
-                            partial_sum += child_size;
-                            if key < partial_sum {
-                                partial_sum -= child_size;
-                                child = ChildRef::Leaf(iter_child);
-                                continue 'get_loop;
-                            }
-
-                            let elem_size = elem.size();
-                            partial_sum += elem_size;
-                            if key < partial_sum {
-                                return Some((partial_sum - elem_size, elem));
-                            }
+                        let elem_end = partial_sum + key.summary();
+                        if value < dim.measure(&elem_end) {
+                            return Some((partial_sum, key, elem_value));
                         }
-                        child = ChildRef::Leaf(children.last().unwrap());
-                    } // for (i, elem) in node.elements().iter().enumerate() {
-                      //     let child_size = node.children().get(i).unwrap().size();
-                      //     partial_sum += child_size;
-                      //     if key < partial_sum {
-                      //         partial_sum -= child_size;
-                      //         child = node.children().get(i).unwrap();
-                      //         continue 'get_loop;
-                      //     }
-
-                      //     let elem_size = elem.size();
-                      //     partial_sum += elem_size;
-                      //     if key < partial_sum {
-                      //         return Some((partial_sum - elem_size, elem));
-                      //     }
-                      // }
-                      // let last_child_idx = node.num_elements();
-                      // child = node.children().get(last_child_idx).unwrap();
-                },
+                        partial_sum = elem_end;
+                    }
+                    child = children.get(node.num_elements()).unwrap();
+                }
                 ChildRef::Leaf(leaf) => {
-                    for elem in leaf.elements().iter() {
-                        let elem_size = elem.size();
-                        partial_sum += elem_size;
-                        if key < partial_sum {
-                            return Some((partial_sum - elem_size, elem));
+                    for (key, elem_value) in leaf.keys().iter().zip(leaf.values()) {
+                        let elem_end = partial_sum + key.summary();
+                        if value < dim.measure(&elem_end) {
+                            return Some((partial_sum, key, elem_value));
                         }
+                        partial_sum = elem_end;
                     }
                     return None;
                 }
@@ -2182,17 +2815,17 @@ impl<K: OrdSize, V> OrdBTree<K, V> {
     // }
 
     pub fn insert(&mut self, pos: usize, key: K, value: V) -> Result<(), (K, V)> {
-        let key_size = key.size();
-        assert!(0 < key_size);
+        let key_summary = key.summary();
+        assert!(0 < key_summary.size());
 
         self.len += 1;
         match &mut self.root {
             Child::Leaf(root) => {
                 let mut partial_sum = 0;
 
-                let overflow_element = 'root_search_and_insert: loop {
-                    for (i, elem) in root.elements().iter().enumerate() {
-                        let elem_size = elem.size();
+                let (overflow_key, overflow_value) = 'root_search_and_insert: {
+                    for (i, elem_key) in root.keys().iter().enumerate() {
+                        let elem_size = elem_key.size();
                         match pos.cmp(&partial_sum) {
                             Ordering::Less => {
                                 self.len -= 1;
@@ -2214,15 +2847,15 @@ impl<K: OrdSize, V> OrdBTree<K, V> {
                         return Err((key, value));
                     }
                     match root.push(key, value) {
-                        Some(overflow) => break 'root_search_and_insert overflow,
+                        Some(overflow) => overflow,
                         None => return Ok(()),
-                    };
+                    }
                 };
 
                 self.depth += 1;
 
                 let mut right = Box::new(NodeElements::new());
-                let sep_element = root.split(overflow_element, right.as_mut());
+                let (sep_key, sep_value) = root.split(overflow_key, overflow_value, right.as_mut());
 
                 let left = mem::replace(root, right);
                 let new_root = Node::new(Child::Leaf(left));
@@ -2233,7 +2866,7 @@ impl<K: OrdSize, V> OrdBTree<K, V> {
                 self.root
                     .try_as_node_mut()
                     .unwrap()
-                    .push(sep_element, Child::Leaf(right))
+                    .push(sep_key, sep_value, Child::Leaf(right))
                     .assert_none();
 
                 Ok(())
@@ -2242,15 +2875,15 @@ impl<K: OrdSize, V> OrdBTree<K, V> {
                 let mut ref_stack = OnStackRefMutStack::<Node<K, V>, 20>::new();
                 ref_stack.push_root(root.as_mut());
 
-                'overflow_loop: loop {
+                'overflow: {
                     let mut children_indices_stack = StackVec::<usize, 20>::new(); // ;
                     let mut partial_sum = 0;
 
-                    let mut overflow_element = 'search_and_insert: loop {
+                    let (mut overflow_key, mut overflow_value) = 'search_and_insert: loop {
                         let node = ref_stack.peek_mut().unwrap();
 
                         let mut child_idx = node.num_elements();
-                        for (i, elem) in node.elements().iter().enumerate() {
+                        for (i, elem_key) in node.keys().iter().enumerate() {
                             let child_size = node.children().get(i).unwrap().size();
                             partial_sum += child_size;
                             if pos <= partial_sum {
@@ -2259,7 +2892,7 @@ impl<K: OrdSize, V> OrdBTree<K, V> {
                                 break;
                             }
 
-                            let elem_size = elem.size();
+                            let elem_size = elem_key.size();
                             partial_sum += elem_size;
                             if pos < partial_sum {
                                 // println!("ERROR(taken_node_element)");
@@ -2277,12 +2910,12 @@ impl<K: OrdSize, V> OrdBTree<K, V> {
                         }) {
                             Ok(check) => assert!(check),
                             Err(leaf) => {
-                                for (i, elem) in leaf.elements().iter().enumerate() {
-                                    let elem_size = elem.size();
+                                for (i, elem_key) in leaf.keys().iter().enumerate() {
+                                    let elem_size = elem_key.size();
                                     match pos.cmp(&partial_sum) {
                                         Ordering::Equal => match leaf.insert(i, key, value) {
                                             Some(overflow) => break 'search_and_insert overflow,
-                                            None => break 'overflow_loop,
+                                            None => break 'overflow,
                                         },
                                         Ordering::Less => {
                                             // println!("ERROR(taken_leaf_element)");
@@ -2300,7 +2933,7 @@ impl<K: OrdSize, V> OrdBTree<K, V> {
                                 }
                                 match leaf.push(key, value) {
                                     Some(overflow) => break 'search_and_insert overflow,
-                                    None => break 'overflow_loop,
+                                    None => break 'overflow,
                                 }
                                 // return None;
                             }
@@ -2317,24 +2950,23 @@ impl<K: OrdSize, V> OrdBTree<K, V> {
                             node.children_mut().try_into_leafs().unwrap()[child_idx].as_mut();
 
                         let mut right = Box::new(NodeElements::new());
-                        let sep_element = child.split(overflow_element, right.as_mut());
-
-                        unsafe {
-                            node.set_size(
-                                node.size() + key_size - sep_element.size() - right.size(),
-                            );
-                        }
+                        let (sep_key, sep_value) =
+                            child.split(overflow_key, overflow_value, right.as_mut());
 
-                        let (rightmost_element, rightmost_child) =
-                            match node.insert(child_idx, sep_element, Child::Leaf(right)) {
+                        // The summary of `node` is stale since `child` split, and summaries can't be
+                        // subtracted, so it's recalculated from its children.
+                        let (rightmost_key, rightmost_value, rightmost_child) =
+                            match node.insert(child_idx, sep_key, sep_value, Child::Leaf(right)) {
                                 Some(x) => x,
                                 None => {
+                                    node.recalc_summary();
                                     ref_stack.pop();
-                                    break 'overflow_loop;
+                                    break 'overflow;
                                 }
                             };
 
-                        overflow_element = rightmost_element;
+                        overflow_key = rightmost_key;
+                        overflow_value = rightmost_value;
                         overflow_child = rightmost_child;
                     }
 
@@ -2345,15 +2977,16 @@ impl<K: OrdSize, V> OrdBTree<K, V> {
 
                                 self.depth += 1;
 
-                                let (sep_element, right) =
-                                    root.split(overflow_element, overflow_child);
+                                let (sep_key, sep_value, right) =
+                                    root.split(overflow_key, overflow_value, overflow_child);
 
                                 let left = mem::replace(root, right);
 
                                 let new_root = Node::new(Child::Node(left));
                                 let right = mem::replace(root, new_root);
 
-                                root.push(sep_element, Child::Node(right)).assert_none();
+                                root.push(sep_key, sep_value, Child::Node(right))
+                                    .assert_none();
 
                                 return Ok(());
                             }
@@ -2365,35 +2998,31 @@ impl<K: OrdSize, V> OrdBTree<K, V> {
                                     [child_idx]
                                     .as_mut();
 
-                                let (sep_element, right) =
-                                    child.split(overflow_element, overflow_child);
-
-                                unsafe {
-                                    node.set_size(
-                                        node.size() + key_size - sep_element.size() - right.size(),
-                                    );
-                                }
-
-                                let (rightmost_element, rightmost_child) =
-                                    match node.insert(child_idx, sep_element, Child::Node(right)) {
-                                        Some(x) => x,
-                                        None => {
-                                            ref_stack.pop();
-                                            break 'overflow_loop;
-                                        }
-                                    };
+                                let (sep_key, sep_value, right) =
+                                    child.split(overflow_key, overflow_value, overflow_child);
+
+                                let (rightmost_key, rightmost_value, rightmost_child) = match node
+                                    .insert(child_idx, sep_key, sep_value, Child::Node(right))
+                                {
+                                    Some(x) => x,
+                                    None => {
+                                        node.recalc_summary();
+                                        ref_stack.pop();
+                                        break 'overflow;
+                                    }
+                                };
 
-                                overflow_element = rightmost_element;
+                                overflow_key = rightmost_key;
+                                overflow_value = rightmost_value;
                                 overflow_child = rightmost_child;
                             }
                         }
                     }
-                    // break;
                 }
 
                 while let Some(node) = ref_stack.peek_mut() {
                     unsafe {
-                        node.set_size(node.size() + key_size);
+                        node.set_summary(node.summary() + key_summary);
                     }
                     ref_stack.pop();
                 }
@@ -2402,13 +3031,13 @@ impl<K: OrdSize, V> OrdBTree<K, V> {
         }
     }
 
-    pub fn remove(&mut self, key: usize) -> Option<(K, V)> {
+    pub fn remove(&mut self, pos: usize) -> Option<(K, V)> {
         fn resolve_underflow<K: OrdSize, V>(node: &mut Node<K, V>, child_idx: usize) {
             debug_assert!(
                 node.children().get(child_idx).unwrap().num_elements() < MIN_NUM_ELEMENTS
             );
 
-            let (elements, children) = node.get_all_mut();
+            let (keys, values, children) = node.get_all_mut();
             match children {
                 ChildrenSliceMut::Nodes(children) => {
                     if let Some(donor_child) = children
@@ -2416,39 +3045,45 @@ impl<K: OrdSize, V> OrdBTree<K, V> {
                         .filter(|child| MIN_NUM_ELEMENTS < child.num_elements())
                     {
                         donor_child.children_mut().swap(0, 1);
-                        let (mut removed_element, removed_child) = donor_child.remove(0);
+                        let (mut removed_key, mut removed_value, removed_child) =
+                            donor_child.remove(0);
 
-                        mem::swap(&mut elements[child_idx], &mut removed_element);
+                        mem::swap(&mut keys[child_idx], &mut removed_key);
+                        mem::swap(&mut values[child_idx], &mut removed_value);
 
                         children[child_idx]
-                            .push(removed_element, removed_child)
+                            .push(removed_key, removed_value, removed_child)
                             .assert_none();
                     } else if let Some(donor_child) = child_idx
                         .checked_sub(1)
                         .map(|i| &mut children[i])
                         .filter(|child| MIN_NUM_ELEMENTS < child.num_elements())
                     {
-                        let (mut removed_element, removed_child) = donor_child.pop().unwrap();
+                        let (mut removed_key, mut removed_value, removed_child) =
+                            donor_child.pop().unwrap();
 
-                        mem::swap(&mut elements[child_idx - 1], &mut removed_element);
+                        mem::swap(&mut keys[child_idx - 1], &mut removed_key);
+                        mem::swap(&mut values[child_idx - 1], &mut removed_value);
 
                         children[child_idx]
-                            .insert(0, removed_element, removed_child)
+                            .insert(0, removed_key, removed_value, removed_child)
                             .assert_none();
                         children[child_idx].children_mut().swap(0, 1);
                     } else {
                         let left = child_idx.saturating_sub(1);
 
-                        let (sep_element, right_child) = node.remove(left);
+                        let (sep_key, sep_value, right_child) = node.remove(left);
                         unsafe {
-                            node.set_size(node.size() + sep_element.size() + right_child.size());
+                            node.set_summary(
+                                node.summary() + sep_key.summary() + right_child.summary(),
+                            );
                         }
 
                         let left_child = &mut node.children_mut().try_into_nodes().unwrap()[left];
 
                         let right_child = right_child.try_into_node().unwrap();
 
-                        left_child.merge(sep_element, right_child);
+                        left_child.merge(sep_key, sep_value, right_child);
                     }
                 }
                 ChildrenSliceMut::Leafs(children) => {
@@ -2456,34 +3091,42 @@ impl<K: OrdSize, V> OrdBTree<K, V> {
                         .get_mut(child_idx + 1)
                         .filter(|child| MIN_NUM_ELEMENTS < child.len())
                     {
-                        let mut removed_element = donor_child.remove(0);
+                        let (mut removed_key, mut removed_value) = donor_child.remove(0);
 
-                        mem::swap(&mut elements[child_idx], &mut removed_element);
+                        mem::swap(&mut keys[child_idx], &mut removed_key);
+                        mem::swap(&mut values[child_idx], &mut removed_value);
 
-                        children[child_idx].push(removed_element).assert_none();
+                        children[child_idx]
+                            .push(removed_key, removed_value)
+                            .assert_none();
                     } else if let Some(donor_child) = child_idx
                         .checked_sub(1)
                         .map(|i| &mut children[i])
                         .filter(|child| MIN_NUM_ELEMENTS < child.len())
                     {
-                        let mut removed_element = donor_child.pop().unwrap();
+                        let (mut removed_key, mut removed_value) = donor_child.pop().unwrap();
 
-                        mem::swap(&mut elements[child_idx - 1], &mut removed_element);
+                        mem::swap(&mut keys[child_idx - 1], &mut removed_key);
+                        mem::swap(&mut values[child_idx - 1], &mut removed_value);
 
-                        children[child_idx].insert(0, removed_element).assert_none();
+                        children[child_idx]
+                            .insert(0, removed_key, removed_value)
+                            .assert_none();
                     } else {
                         let left = child_idx.saturating_sub(1);
 
-                        let (sep_element, right_child) = node.remove(left);
+                        let (sep_key, sep_value, right_child) = node.remove(left);
                         unsafe {
-                            node.set_size(node.size() + sep_element.size() + right_child.size());
+                            node.set_summary(
+                                node.summary() + sep_key.summary() + right_child.summary(),
+                            );
                         }
 
                         let left_child = &mut node.children_mut().try_into_leafs().unwrap()[left];
 
                         let mut right_child = right_child.try_into_leaf().unwrap();
 
-                        left_child.merge(sep_element, right_child.as_mut());
+                        left_child.merge(sep_key, sep_value, right_child.as_mut());
                     }
                 }
             }
@@ -2496,14 +3139,14 @@ impl<K: OrdSize, V> OrdBTree<K, V> {
         match &mut self.root {
             Child::Leaf(root) => {
                 let mut partial_sum = 0;
-                for (i, elem) in root.elements().iter().enumerate() {
-                    let elem_size = elem.size();
-                    if key == partial_sum {
+                for (i, elem_key) in root.keys().iter().enumerate() {
+                    let elem_size = elem_key.size();
+                    if pos == partial_sum {
                         self.len -= 1;
                         return Some(root.remove(i));
                     }
                     partial_sum += elem_size;
-                    if key < partial_sum {
+                    if pos < partial_sum {
                         return None;
                     }
                 }
@@ -2517,15 +3160,15 @@ impl<K: OrdSize, V> OrdBTree<K, V> {
                 let mut children_indices_stack = StackVec::<_, 20>::new(); // ;
                 ref_stack.push_root(root.as_mut());
 
-                let removed_element = 'search_and_remove: loop {
+                let (removed_key, removed_value) = 'search_and_remove: loop {
                     let node = ref_stack.peek_mut().unwrap();
 
                     let mut child_idx = node.num_elements();
-                    for (i, elem) in node.elements().iter().enumerate() {
+                    for (i, elem_key) in node.keys().iter().enumerate() {
                         let child_size = node.children().get(i).unwrap().size();
                         partial_sum += child_size;
 
-                        match key.cmp(&partial_sum) {
+                        match pos.cmp(&partial_sum) {
                             Ordering::Less => {
                                 partial_sum -= child_size;
                                 child_idx = i;
@@ -2534,7 +3177,7 @@ impl<K: OrdSize, V> OrdBTree<K, V> {
                             Ordering::Equal => {
                                 children_indices_stack.push(i).assert_none();
 
-                                let replacement_element =
+                                let (replacement_key, replacement_value) =
                                     match ref_stack.try_push(|node| match node.children_mut() {
                                         ChildrenSliceMut::Nodes(nodes) => Ok(nodes[i].as_mut()),
                                         ChildrenSliceMut::Leafs(leafs) => Err(leafs[i].as_mut()),
@@ -2561,7 +3204,6 @@ impl<K: OrdSize, V> OrdBTree<K, V> {
                                         }
                                         Err(leaf) => leaf.pop().unwrap(),
                                     };
-                                let replacement_element_size = replacement_element.size();
 
                                 while children_indices_stack.len() < ref_stack.len() {
                                     let node = ref_stack.peek_mut().unwrap();
@@ -2573,53 +3215,48 @@ impl<K: OrdSize, V> OrdBTree<K, V> {
                                         .num_elements()
                                         < MIN_NUM_ELEMENTS
                                     {
-                                        unsafe {
-                                            node.set_size(node.size() - replacement_element_size);
-                                        }
+                                        node.recalc_summary();
                                         resolve_underflow(node, node.num_elements());
                                         ref_stack.pop().assert_none();
                                     } else {
                                         while children_indices_stack.len() < ref_stack.len() {
                                             let node = ref_stack.peek_mut().unwrap();
 
-                                            unsafe {
-                                                node.set_size(
-                                                    node.size() - replacement_element_size,
-                                                );
-                                            }
+                                            node.recalc_summary();
                                             ref_stack.pop().assert_none();
                                         }
 
                                         let node = ref_stack.peek_mut().unwrap();
-                                        let removed_element = mem::replace(
-                                            &mut node.elements_mut()[i],
-                                            replacement_element,
+                                        let removed_key =
+                                            mem::replace(&mut node.keys_mut()[i], replacement_key);
+                                        let removed_value = mem::replace(
+                                            &mut node.values_mut()[i],
+                                            replacement_value,
                                         );
-                                        let removed_element_size = removed_element.size();
                                         while let Some(node) = ref_stack.peek_mut() {
-                                            unsafe {
-                                                node.set_size(node.size() - removed_element_size);
-                                            }
+                                            node.recalc_summary();
                                             ref_stack.pop();
                                         }
 
                                         self.len -= 1;
-                                        return Some(removed_element);
+                                        return Some((removed_key, removed_value));
                                     }
                                 }
 
                                 let node = ref_stack.peek_mut().unwrap();
-                                let removed_element =
-                                    mem::replace(&mut node.elements_mut()[i], replacement_element);
+                                let removed_key =
+                                    mem::replace(&mut node.keys_mut()[i], replacement_key);
+                                let removed_value =
+                                    mem::replace(&mut node.values_mut()[i], replacement_value);
 
-                                break 'search_and_remove (removed_element);
+                                break 'search_and_remove (removed_key, removed_value);
                             }
                             Ordering::Greater => {}
                         }
 
-                        let elem_size = elem.size();
+                        let elem_size = elem_key.size();
                         partial_sum += elem_size;
-                        if key < partial_sum {
+                        if pos < partial_sum {
                             return None;
                         }
                     }
@@ -2631,27 +3268,24 @@ impl<K: OrdSize, V> OrdBTree<K, V> {
                     }) {
                         Ok(success) => assert!(success),
                         Err(leaf) => {
-                            for (i, elem) in leaf.elements().iter().enumerate() {
-                                let elem_size = elem.size();
-                                if partial_sum == key {
+                            for (i, elem_key) in leaf.keys().iter().enumerate() {
+                                let elem_size = elem_key.size();
+                                if partial_sum == pos {
                                     if leaf.len() == MIN_NUM_ELEMENTS {
                                         break 'search_and_remove leaf.remove(i);
                                     } else {
-                                        let removed_element = leaf.remove(i);
-                                        let removed_element_size = removed_element.size();
+                                        let (removed_key, removed_value) = leaf.remove(i);
 
                                         while let Some(node) = ref_stack.peek_mut() {
-                                            unsafe {
-                                                node.set_size(node.size() - removed_element_size);
-                                            }
+                                            node.recalc_summary();
                                             ref_stack.pop();
                                         }
                                         self.len -= 1;
-                                        return Some(removed_element);
+                                        return Some((removed_key, removed_value));
                                     }
                                 }
                                 partial_sum += elem_size;
-                                if key < partial_sum {
+                                if pos < partial_sum {
                                     return None;
                                 }
                             }
@@ -2659,26 +3293,21 @@ impl<K: OrdSize, V> OrdBTree<K, V> {
                         }
                     }
                 };
-                let removed_element_size = removed_element.size();
 
                 loop {
                     let node = ref_stack.peek_mut().unwrap();
                     let child_idx = children_indices_stack.pop().unwrap();
 
                     if node.children().get(child_idx).unwrap().num_elements() < MIN_NUM_ELEMENTS {
-                        unsafe {
-                            node.set_size(node.size() - removed_element_size);
-                        }
+                        node.recalc_summary();
                         resolve_underflow(node, child_idx);
                     } else {
                         while let Some(node) = ref_stack.peek_mut() {
-                            unsafe {
-                                node.set_size(node.size() - removed_element_size);
-                            }
+                            node.recalc_summary();
                             ref_stack.pop();
                         }
                         self.len -= 1;
-                        return Some(removed_element);
+                        return Some((removed_key, removed_value));
                     }
 
                     if let Some(_root) = ref_stack.pop() {
@@ -2689,39 +3318,61 @@ impl<K: OrdSize, V> OrdBTree<K, V> {
                             assert!(self.root.replace_with_child());
                         }
                         self.len -= 1;
-                        return Some(removed_element);
+                        return Some((removed_key, removed_value));
                     }
                 }
             }
         }
     }
 
-    // pub fn check_sizes(&self) {
-    //     fn check_sizes_rec<T: OrdSize>(child: ChildRef<T>) -> usize {
-    //         match child {
-    //             ChildRef::Node(node) => {
-    //                 let node_elements_size: usize =
-    //                     node.elements().iter().map(|elem| elem.size()).sum();
-    //                 let node_children_size: usize = node
-    //                     .children()
-    //                     .iter()
-    //                     .map(|child| check_sizes_rec(child))
-    //                     .sum();
-    //                 assert_eq!(node.size(), node_elements_size + node_children_size);
+    /// Panics if the tree is malformed: a node's summary doesn't match its contents, a non-root
+    /// node has fewer than `MIN_NUM_ELEMENTS` elements, the leafs aren't all at the same depth,
+    /// or `len` doesn't match the number of elements.
+    pub(crate) fn check_invariants(&self)
+    where
+        K::Summary: PartialEq,
+    {
+        fn check_invariants_rec<K: OrdSize, V>(
+            child: ChildRef<K, V>,
+            height: usize,
+            is_root: bool,
+        ) -> (K::Summary, usize)
+        where
+            K::Summary: PartialEq,
+        {
+            let num_elements = child.num_elements();
+            assert!(num_elements <= MAX_NUM_ELEMENTS);
+            if !is_root {
+                assert!(MIN_NUM_ELEMENTS <= num_elements);
+            }
 
-    //                 node.size()
-    //             }
-    //             ChildRef::Leaf(leaf) => {
-    //                 let leaf_elements_size = leaf.elements().iter().map(|elem| elem.size()).sum();
-    //                 assert_eq!(leaf.size(), leaf_elements_size);
-    //                 leaf.size()
-    //             }
-    //         }
-    //     }
-    //     check_sizes_rec(self.root.as_ref());
-    // }
+            let mut summary = child
+                .keys()
+                .iter()
+                .fold(K::Summary::default(), |acc, key| acc + key.summary());
+            let mut len = num_elements;
+            match child {
+                ChildRef::Node(node) => {
+                    assert!(0 < height && 0 < num_elements);
+                    for child in node.children().iter() {
+                        let (child_summary, child_len) =
+                            check_invariants_rec(child, height - 1, false);
+                        summary = summary + child_summary;
+                        len += child_len;
+                    }
+                }
+                ChildRef::Leaf(_) => assert_eq!(height, 0),
+            }
+            assert_eq!(child.summary(), summary);
 
-    pub fn iter(&self) -> BTreeIter<K, V> {
+            (summary, len)
+        }
+
+        let (_, len) = check_invariants_rec(self.root.as_ref(), self.depth - 1, true);
+        assert_eq!(self.len, len);
+    }
+
+    pub fn iter(&self) -> BTreeIter<'_, K, V> {
         let mut left = Vec::with_capacity(self.depth());
         left.push((self.root.as_ref(), 0));
         while let Some(&(ChildRef::Node(node), _)) = left.last() {
@@ -2742,6 +3393,166 @@ impl<K: OrdSize, V> OrdBTree<K, V> {
         }
     }
 
+    /// Iterates over the elements that overlap `range`, together with their start positions.
+    ///
+    /// O(log n) to find the first element.
+    pub fn range<R: RangeBounds<usize>>(&self, range: R) -> BTreeRange<'_, K, V> {
+        let (start, end) = bounds(range, self.size());
+
+        let mut stack = Vec::with_capacity(self.depth());
+        let mut partial_sum = 0;
+        if start < end {
+            let mut child = self.root.as_ref();
+
+            'seek_loop: loop {
+                match child {
+                    ChildRef::Node(node) => {
+                        let children = node.children();
+                        let mut child_idx = node.num_elements();
+                        for (i, key) in node.keys().iter().enumerate() {
+                            let child_size = children.get(i).unwrap().size();
+                            if start < partial_sum + child_size {
+                                child_idx = i;
+                                break;
+                            }
+                            partial_sum += child_size;
+
+                            if start < partial_sum + key.size() {
+                                stack.push((child, i));
+                                break 'seek_loop;
+                            }
+                            partial_sum += key.size();
+                        }
+
+                        stack.push((child, child_idx));
+                        child = children.get(child_idx).unwrap();
+                    }
+                    ChildRef::Leaf(leaf) => {
+                        for (i, key) in leaf.keys().iter().enumerate() {
+                            if start < partial_sum + key.size() {
+                                stack.push((child, i));
+                                break 'seek_loop;
+                            }
+                            partial_sum += key.size();
+                        }
+                        unreachable!();
+                    }
+                }
+            }
+        }
+
+        BTreeRange {
+            stack,
+            pos: partial_sum,
+            end,
+        }
+    }
+
+    /// Returns true if an element starts at `pos`, or if `pos` is the end of the tree.
+    fn is_boundary(&self, pos: usize) -> bool {
+        pos == self.size() || matches!(self.get(pos), Some((start, _, _)) if start == pos)
+    }
+
+    /// Replaces the elements between the positions `start` and `end` (including the elements that
+    /// straddle them) with `pairs`, and returns the removed elements.
+    fn splice_pairs(&mut self, start: usize, end: usize, pairs: Vec<(K, V)>) -> Vec<(K, V)> {
+        assert!(pairs.iter().all(|(key, _)| 0 < key.size()));
+
+        let root = mem::replace(&mut self.root, Child::Leaf(Box::default()));
+
+        let ((left, left_height), (rest, rest_height)) = root.split(self.depth - 1, start, false);
+        let ((mid, _), (right, right_height)) = rest.split(rest_height, end - left.size(), true);
+
+        let mut removed = Vec::new();
+        mid.append_to(&mut removed);
+        self.len = self.len - removed.len() + pairs.len();
+
+        let (root, height) = left.join_pairs(left_height, pairs, right, right_height);
+        self.root = root;
+        self.depth = height + 1;
+
+        removed
+    }
+
+    /// Removes the elements that overlap `range` and returns them in order.
+    /// The tree is split around the range and joined back together, so the cost doesn't depend on
+    /// rebalancing after every removed element.
+    ///
+    /// O(log n + k)
+    pub fn drain<R: RangeBounds<usize>>(&mut self, range: R) -> BTreeDrain<K, V> {
+        let (start, end) = bounds(range, self.size());
+
+        let drained = if start < end {
+            self.splice_pairs(start, end, Vec::new())
+        } else {
+            Vec::new()
+        };
+
+        BTreeDrain {
+            iter: drained.into_iter(),
+        }
+    }
+
+    /// Inserts all the elements of `iter` at `pos`, which must be the start of an element or the
+    /// end of the tree, otherwise `iter` is returned untouched.
+    /// The elements are built into a balanced subtree which is grafted into the tree.
+    ///
+    /// O(log n + k)
+    pub fn insert_many<I: IntoIterator<Item = (K, V)>>(
+        &mut self,
+        pos: usize,
+        iter: I,
+    ) -> Result<(), I> {
+        if !self.is_boundary(pos) {
+            return Err(iter);
+        }
+
+        let pairs: Vec<_> = iter.into_iter().collect();
+        if !pairs.is_empty() {
+            let removed = self.splice_pairs(pos, pos, pairs);
+            debug_assert!(removed.is_empty());
+        }
+        Ok(())
+    }
+
+    /// Replaces the elements that overlap `range` with the elements of `iter`, and returns the
+    /// removed elements in order.
+    /// Panics if `range` is empty and doesn't lie between two elements.
+    ///
+    /// O(log n + k)
+    pub fn splice<R, I>(&mut self, range: R, iter: I) -> BTreeDrain<K, V>
+    where
+        R: RangeBounds<usize>,
+        I: IntoIterator<Item = (K, V)>,
+    {
+        let (start, end) = bounds(range, self.size());
+        assert!(
+            start < end || self.is_boundary(start),
+            "splice position {} is inside an element",
+            start
+        );
+
+        let removed = self.splice_pairs(start, end, iter.into_iter().collect());
+        BTreeDrain {
+            iter: removed.into_iter(),
+        }
+    }
+
+    /// Replaces the element that starts at `pos` and returns it.
+    /// Returns the given element back if no element starts at `pos`.
+    ///
+    /// O(log n)
+    pub fn replace(&mut self, pos: usize, key: K, value: V) -> Result<(K, V), (K, V)> {
+        if pos == self.size() || !self.is_boundary(pos) {
+            return Err((key, value));
+        }
+
+        Ok(self
+            .splice_pairs(pos, pos + 1, vec![(key, value)])
+            .pop()
+            .unwrap())
+    }
+
     // pub fn iter_mut(&mut self) -> BTreeIterMut<K, V> {
     //     unsafe {
     //         let mut left = Vec::with_capacity(self.depth());
@@ -2800,6 +3611,37 @@ impl<K: OrdSize, V> Default for OrdBTree<K, V> {
     }
 }
 
+/// Yields the element at the top of `stack` and advances `stack` to the next element in order.
+/// Every entry of `stack` is a child and the index of the next element to yield in it.
+fn step_forward<'a, K: OrdSize, V>(
+    stack: &mut Vec<(ChildRef<'a, K, V>, usize)>,
+) -> Option<(&'a K, &'a V)> {
+    let (child, elem_idx) = stack.last_mut()?;
+    let child = *child;
+    let item = (&child.keys()[*elem_idx], &child.values()[*elem_idx]);
+
+    *elem_idx += 1;
+    let child_idx = *elem_idx;
+    match child {
+        ChildRef::Node(node) => {
+            stack.push((node.children().get(child_idx).unwrap(), 0));
+            while let Some(&(ChildRef::Node(node), _)) = stack.last() {
+                stack.push((node.children().get(0).unwrap(), 0));
+            }
+        }
+        ChildRef::Leaf(_) => {
+            while let Some(&(child, elem_idx)) = stack.last() {
+                if elem_idx < child.num_elements() {
+                    break;
+                }
+                stack.pop();
+            }
+        }
+    }
+
+    Some(item)
+}
+
 #[derive(Clone, Debug)]
 pub struct BTreeIter<'a, K: OrdSize, V> {
     left: Vec<(ChildRef<'a, K, V>, usize)>,
@@ -2813,26 +3655,7 @@ impl<'a, K: OrdSize, V> Iterator for BTreeIter<'a, K, V> {
     fn next(&mut self) -> Option<Self::Item> {
         if 0 < self.len {
             self.len -= 1;
-            let (child, elem_idx) = self.left.last_mut().unwrap();
-            let item = &child.elements()[*elem_idx];
-
-            *elem_idx += 1;
-            match *child {
-                ChildRef::Node(_) => {
-                    while let Some(&(ChildRef::Node(node), child_idx)) = self.left.last() {
-                        self.left.push((node.children().get(child_idx).unwrap(), 0));
-                    }
-                }
-                ChildRef::Leaf(_) => {
-                    while let Some(&(child, elem_idx)) = self.left.last() {
-                        if child.num_elements() <= elem_idx {
-                            self.left.pop();
-                        }
-                    }
-                }
-            }
-
-            Some(item)
+            step_forward(&mut self.left)
         } else {
             None
         }
@@ -2852,21 +3675,23 @@ impl<'a, K: OrdSize, V> DoubleEndedIterator for BTreeIter<'a, K, V> {
         if 0 < self.len {
             self.len -= 1;
             let (child, elem_idx) = self.right.last_mut().unwrap();
+            let child = *child;
             *elem_idx -= 1;
-            let item = &child.elements()[*elem_idx];
+            let child_idx = *elem_idx;
+            let item = (&child.keys()[child_idx], &child.values()[child_idx]);
 
-            match *child {
-                ChildRef::Node(_) => {
+            match child {
+                ChildRef::Node(node) => {
+                    let child = node.children().get(child_idx).unwrap();
+                    self.right.push((child, child.num_elements()));
                     while let Some(&(ChildRef::Node(node), child_idx)) = self.right.last() {
                         let child = node.children().get(child_idx).unwrap();
                         self.right.push((child, child.num_elements()));
                     }
                 }
                 ChildRef::Leaf(_) => {
-                    while let Some(&(_, elem_idx)) = self.right.last() {
-                        if elem_idx == 0 {
-                            self.right.pop();
-                        }
+                    while let Some(&(_, 0)) = self.right.last() {
+                        self.right.pop();
                     }
                 }
             }
@@ -2884,6 +3709,65 @@ impl<'a, K: OrdSize, V> ExactSizeIterator for BTreeIter<'a, K, V> {
     }
 }
 
+#[derive(Clone, Debug)]
+pub struct BTreeRange<'a, K: OrdSize, V> {
+    stack: Vec<(ChildRef<'a, K, V>, usize)>,
+    pos: usize,
+    end: usize,
+}
+
+impl<'a, K: OrdSize, V> Iterator for BTreeRange<'a, K, V> {
+    type Item = (usize, &'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.pos < self.end {
+            let (key, value) = step_forward(&mut self.stack)?;
+            let start = self.pos;
+            self.pos += key.size();
+            Some((start, key, value))
+        } else {
+            None
+        }
+    }
+}
+
+impl<'a, K: OrdSize, V> std::iter::FusedIterator for BTreeRange<'a, K, V> {}
+
+#[derive(Debug)]
+pub struct BTreeDrain<K, V> {
+    iter: vec::IntoIter<(K, V)>,
+}
+
+impl<K, V> Iterator for BTreeDrain<K, V> {
+    type Item = (K, V);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<K, V> DoubleEndedIterator for BTreeDrain<K, V> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.iter.next_back()
+    }
+}
+
+impl<K, V> ExactSizeIterator for BTreeDrain<K, V> {
+    #[inline]
+    fn len(&self) -> usize {
+        self.iter.len()
+    }
+}
+
+impl<K, V> std::iter::FusedIterator for BTreeDrain<K, V> {}
+
 // #[derive(Debug)]
 // pub struct BTreeIterMut<'a, K: Ord, V> {
 //     left: Vec<(ChildPtrMut<K, V>, usize)>,
@@ -0,0 +1,287 @@
+use crate::groups::{AbelianGroup, Monoid};
+
+/// The node which every version starts from, its children are itself and its sum is the identity.
+const EMPTY: usize = 0;
+
+#[derive(Clone, Debug)]
+struct Node<T> {
+    sum: T,
+    children: [usize; 2],
+}
+
+/// A handle to a version of a `PersistentSegmentTree`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct SegmentTreeVersion(usize);
+
+/// A segment tree in which every update creates a new version, which shares the untouched nodes
+/// with the version it was made from.
+#[derive(Clone, Debug)]
+pub struct PersistentSegmentTree<G: Monoid> {
+    group: G,
+    len: usize,
+    nodes: Vec<Node<G::Elem>>,
+}
+
+impl<G: Monoid> PersistentSegmentTree<G> {
+    #[inline]
+    pub fn new(group: G, length: usize) -> Self {
+        let empty = Node {
+            sum: group.id(),
+            children: [EMPTY; 2],
+        };
+
+        Self {
+            group,
+            len: length,
+            nodes: vec![empty],
+        }
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The number of nodes in all the versions together.
+    #[inline]
+    pub fn num_nodes(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// The version in which all the elements are the identity.
+    ///
+    /// O(1)
+    #[inline]
+    pub fn empty(&self) -> SegmentTreeVersion {
+        SegmentTreeVersion(EMPTY)
+    }
+
+    fn push_node(&mut self, left: usize, right: usize) -> usize {
+        let sum = self
+            .group
+            .add(self.nodes[left].sum.clone(), self.nodes[right].sum.clone());
+        self.nodes.push(Node {
+            sum,
+            children: [left, right],
+        });
+        self.nodes.len() - 1
+    }
+
+    /// Creates a version whose first elements are the elements of `iter`.
+    /// Panics if `iter` is longer than the tree, like `SegmentTree::build`.
+    ///
+    /// O(n)
+    pub fn build<Iter: IntoIterator<Item = G::Elem>>(&mut self, iter: Iter) -> SegmentTreeVersion {
+        fn build_rec<G: Monoid, Iter: Iterator<Item = G::Elem>>(
+            slf: &mut PersistentSegmentTree<G>,
+            lo: usize,
+            hi: usize,
+            iter: &mut Iter,
+        ) -> usize {
+            if hi - lo == 1 {
+                match iter.next() {
+                    Some(x) => {
+                        slf.nodes.push(Node {
+                            sum: x,
+                            children: [EMPTY; 2],
+                        });
+                        slf.nodes.len() - 1
+                    }
+                    None => EMPTY,
+                }
+            } else {
+                let mid = (lo + hi) / 2;
+                let left = build_rec(slf, lo, mid, iter);
+                let right = build_rec(slf, mid, hi, iter);
+                slf.push_node(left, right)
+            }
+        }
+
+        let mut iter = iter.into_iter();
+        let version = if self.is_empty() {
+            self.empty()
+        } else {
+            SegmentTreeVersion(build_rec(self, 0, self.len(), &mut iter))
+        };
+        assert!(iter.next().is_none(), "`iter` is longer than the tree");
+        version
+    }
+
+    /// Sums the elements in `l..r` of `version` in order.
+    ///
+    /// O(log n)
+    pub fn sum(&self, version: SegmentTreeVersion, l: usize, r: usize) -> G::Elem {
+        fn sum_rec<G: Monoid>(
+            slf: &PersistentSegmentTree<G>,
+            node: usize,
+            lo: usize,
+            hi: usize,
+            l: usize,
+            r: usize,
+        ) -> G::Elem {
+            if r <= lo || hi <= l || node == EMPTY {
+                return slf.group.id();
+            }
+            if l <= lo && hi <= r {
+                return slf.nodes[node].sum.clone();
+            }
+
+            let mid = (lo + hi) / 2;
+            let [left, right] = slf.nodes[node].children;
+            slf.group.add(
+                sum_rec(slf, left, lo, mid, l, r),
+                sum_rec(slf, right, mid, hi, l, r),
+            )
+        }
+
+        assert!(l <= r, "range start {} is greater than range end {}", l, r);
+        assert!(
+            r <= self.len(),
+            "range end {} is out of bounds for length {}",
+            r,
+            self.len()
+        );
+        if l < r {
+            sum_rec(self, version.0, 0, self.len(), l, r)
+        } else {
+            self.group.id()
+        }
+    }
+
+    /// Creates a version of `version` in which the `i`th element is `x`.
+    ///
+    /// O(log n)
+    pub fn update(
+        &mut self,
+        version: SegmentTreeVersion,
+        i: usize,
+        x: G::Elem,
+    ) -> SegmentTreeVersion {
+        fn update_rec<G: Monoid>(
+            slf: &mut PersistentSegmentTree<G>,
+            node: usize,
+            lo: usize,
+            hi: usize,
+            i: usize,
+            x: G::Elem,
+        ) -> usize {
+            if hi - lo == 1 {
+                slf.nodes.push(Node {
+                    sum: x,
+                    children: [EMPTY; 2],
+                });
+                return slf.nodes.len() - 1;
+            }
+
+            let mid = (lo + hi) / 2;
+            let [mut left, mut right] = slf.nodes[node].children;
+            if i < mid {
+                left = update_rec(slf, left, lo, mid, i, x);
+            } else {
+                right = update_rec(slf, right, mid, hi, i, x);
+            }
+            slf.push_node(left, right)
+        }
+
+        assert!(
+            i < self.len(),
+            "index {} is out of bounds for length {}",
+            i,
+            self.len()
+        );
+        SegmentTreeVersion(update_rec(self, version.0, 0, self.len(), i, x))
+    }
+
+    /// Creates a version of `version` in which `x` is added to the `i`th element.
+    ///
+    /// O(log n)
+    pub fn update_add(
+        &mut self,
+        version: SegmentTreeVersion,
+        i: usize,
+        x: G::Elem,
+    ) -> SegmentTreeVersion {
+        let y = self.group.add(self.get(version, i).clone(), x);
+        self.update(version, i, y)
+    }
+
+    /// O(log n)
+    pub fn get(&self, version: SegmentTreeVersion, i: usize) -> &G::Elem {
+        assert!(
+            i < self.len(),
+            "index {} is out of bounds for length {}",
+            i,
+            self.len()
+        );
+
+        let (mut lo, mut hi) = (0, self.len());
+        let mut node = version.0;
+        while hi - lo != 1 {
+            let mid = (lo + hi) / 2;
+            let [left, right] = self.nodes[node].children;
+            if i < mid {
+                node = left;
+                hi = mid;
+            } else {
+                node = right;
+                lo = mid;
+            }
+        }
+        &self.nodes[node].sum
+    }
+}
+
+impl<G: AbelianGroup> PersistentSegmentTree<G>
+where
+    G::Elem: PartialOrd,
+{
+    /// Returns the smallest `i` such that the sum of the elements in `0..=i` of `after` minus
+    /// those of `before` is greater than `k`, or `None` if there's no such `i`.
+    ///
+    /// When the elements are counts and `after` was made from `before` by counting the (compressed)
+    /// values of a range, this is the `k`th smallest value in the range.
+    ///
+    /// O(log n)
+    pub fn kth(
+        &self,
+        before: SegmentTreeVersion,
+        after: SegmentTreeVersion,
+        mut k: G::Elem,
+    ) -> Option<usize> {
+        let (mut lo, mut hi) = (0, self.len());
+        let (mut b, mut a) = (before.0, after.0);
+
+        let total = self
+            .group
+            .sub(self.nodes[a].sum.clone(), self.nodes[b].sum.clone());
+        if self.is_empty() || total <= k {
+            return None;
+        }
+
+        while hi - lo != 1 {
+            let mid = (lo + hi) / 2;
+            let [bl, br] = self.nodes[b].children;
+            let [al, ar] = self.nodes[a].children;
+
+            let left = self
+                .group
+                .sub(self.nodes[al].sum.clone(), self.nodes[bl].sum.clone());
+            if k < left {
+                b = bl;
+                a = al;
+                hi = mid;
+            } else {
+                k = self.group.sub(k, left);
+                b = br;
+                a = ar;
+                lo = mid;
+            }
+        }
+        Some(lo)
+    }
+}
@@ -0,0 +1,94 @@
+use crate::{
+    groups::{AbelianGroup, ScalarMultiplication},
+    segment_tree::bounds,
+    FenwickTree,
+};
+use std::fmt;
+use std::ops::RangeBounds;
+
+/// A Fenwick tree with range updates and range queries, made of two Fenwick trees `b1` and `b2`
+/// such that the sum of `0..i` is `b1.prefix_sum(i) * i - b2.prefix_sum(i)`.
+#[derive(Clone)]
+pub struct RangeFenwickTree<G: AbelianGroup + ScalarMultiplication> {
+    group: G,
+    b1: FenwickTree<G>,
+    b2: FenwickTree<G>,
+}
+
+impl<G: AbelianGroup + ScalarMultiplication + Clone> RangeFenwickTree<G> {
+    /// Creates a tree of `length` identities.
+    pub fn new(group: G, length: usize) -> Self {
+        let mut b1 = FenwickTree::new(group.clone());
+        b1.resize_id(length);
+        let mut b2 = FenwickTree::new(group.clone());
+        b2.resize_id(length);
+
+        Self { group, b1, b2 }
+    }
+}
+
+impl<G: AbelianGroup + ScalarMultiplication> RangeFenwickTree<G> {
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.b1.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Adds `dx` to every element in `range`.
+    ///
+    /// O(log n)
+    pub fn range_add<R: RangeBounds<usize>>(&mut self, range: R, dx: G::Elem) {
+        let (l, r) = bounds(range, self.len());
+        if l == r {
+            return;
+        }
+
+        let neg_dx = self.group.inv(dx.clone());
+        self.b1.update_add(l, dx.clone());
+        self.b1.update_add(r, neg_dx.clone());
+        self.b2.update_add(l, self.group.mul_scalar(dx, l));
+        self.b2.update_add(r, self.group.mul_scalar(neg_dx, r));
+    }
+
+    /// Sums the elements in `0..i`.
+    ///
+    /// O(log n)
+    pub fn prefix_sum(&self, i: usize) -> G::Elem {
+        let s = self.group.mul_scalar(self.b1.prefix_sum(i), i);
+        self.group.sub(s, self.b2.prefix_sum(i))
+    }
+
+    /// O(log n)
+    pub fn range_sum<R: RangeBounds<usize>>(&self, range: R) -> G::Elem {
+        let (l, r) = bounds(range, self.len());
+        self.group.sub(self.prefix_sum(r), self.prefix_sum(l))
+    }
+
+    /// O(log n)
+    pub fn get(&self, i: usize) -> G::Elem {
+        assert!(
+            i < self.len(),
+            "index {} is out of bounds for length {}",
+            i,
+            self.len()
+        );
+        self.range_sum(i..=i)
+    }
+}
+
+impl<G: AbelianGroup + ScalarMultiplication + fmt::Debug> fmt::Debug for RangeFenwickTree<G>
+where
+    G::Elem: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RangeFenwickTree")
+            .field("group", &self.group)
+            .field("b1", &self.b1)
+            .field("b2", &self.b2)
+            .finish()
+    }
+}
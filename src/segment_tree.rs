@@ -1,20 +1,87 @@
-use crate::groups::CommutativeMonoid;
+use crate::{groups::Monoid, StackVec};
+use std::ops::{Bound, RangeBounds};
 
+/// The start and end of `range` over `0..len`, or `None` if one of them overflows.
+pub(crate) fn try_bounds<R: RangeBounds<usize>>(range: R, len: usize) -> Option<(usize, usize)> {
+    let l = match range.start_bound() {
+        Bound::Included(&l) => l,
+        Bound::Excluded(&l) => l.checked_add(1)?,
+        Bound::Unbounded => 0,
+    };
+    let r = match range.end_bound() {
+        Bound::Included(&r) => r.checked_add(1)?,
+        Bound::Excluded(&r) => r,
+        Bound::Unbounded => len,
+    };
+    Some((l, r))
+}
+
+/// Like `try_bounds`, but panics if `range` isn't a valid range of `0..len`.
+pub(crate) fn bounds<R: RangeBounds<usize>>(range: R, len: usize) -> (usize, usize) {
+    let (l, r) = match try_bounds(range, len) {
+        Some(bounds) => bounds,
+        None => panic!("range is out of bounds for length {}", len),
+    };
+    assert!(l <= r, "range start {} is greater than range end {}", l, r);
+    assert!(
+        r <= len,
+        "range end {} is out of bounds for length {}",
+        r,
+        len
+    );
+
+    (l, r)
+}
+
+/// The elements are the leaves of a `2 * capacity - 1` node heap, where the leaves after `len`
+/// are the identity.
 #[derive(Clone, Debug)]
-pub struct SegmentTree<G: CommutativeMonoid> {
+pub struct SegmentTree<G: Monoid> {
     group: G,
+    len: usize,
     data: Vec<G::Elem>,
 }
 
-impl<G: CommutativeMonoid> SegmentTree<G> {
+impl<G: Monoid> SegmentTree<G> {
     #[inline]
     pub fn new(group: G, length: usize) -> Self {
+        let mut slf = Self::with_capacity(group, length);
+        slf.len = length;
+        slf
+    }
+
+    /// Constructs a new, empty `SegmentTree<G>` with the specified capacity.
+    #[inline]
+    pub fn with_capacity(group: G, capacity: usize) -> Self {
         let mut data = Vec::new();
-        data.resize_with(2 * length - 1, || group.id());
-        Self { group, data }
+        data.resize_with((2 * capacity).saturating_sub(1), || group.id());
+        Self {
+            group,
+            len: 0,
+            data,
+        }
+    }
+
+    /// O(n)
+    pub fn from_iter_in<Iter: IntoIterator<Item = G::Elem>>(group: G, iter: Iter) -> Self {
+        let elements: Vec<_> = iter.into_iter().collect();
+        let mut slf = Self::new(group, elements.len());
+        slf.build(elements);
+        slf
     }
 
+    #[inline]
     pub fn len(&self) -> usize {
+        self.len
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    #[inline]
+    pub fn capacity(&self) -> usize {
         (self.data.len() + 1) / 2
     }
 
@@ -22,45 +89,290 @@ impl<G: CommutativeMonoid> SegmentTree<G> {
         self.data.len() / 2
     }
 
+    /// Recalculates all the inner nodes from the leaves.
+    fn rebuild(&mut self) {
+        for i in (0..self.start()).rev() {
+            let l = 2 * i + 1;
+            let r = l + 1;
+            self.data[i] = self.group.add(self.data[l].clone(), self.data[r].clone());
+        }
+    }
+
+    /// Moves the elements to a tree with room for `capacity` elements.
+    ///
+    /// O(capacity)
+    fn reallocate(&mut self, capacity: usize) {
+        let start = self.start();
+        let mut data = Vec::with_capacity(2 * capacity - 1);
+        data.resize_with(capacity - 1, || self.group.id());
+        data.extend(self.data.drain(start..start + self.len));
+        data.resize_with(2 * capacity - 1, || self.group.id());
+
+        self.data = data;
+        self.rebuild();
+    }
+
+    /// Reserves capacity for at least `additional` more elements. The capacity is at least
+    /// doubled, so pushing stays O(log n) amortized.
+    pub fn reserve(&mut self, additional: usize) {
+        let capacity = self.len + additional;
+        if self.capacity() < capacity {
+            self.reallocate(capacity.max(2 * self.capacity()));
+        }
+    }
+
     /// O(n)
     pub fn build<Iter: IntoIterator<Item = G::Elem>>(&mut self, iter: Iter) {
         let start = self.start();
         for (i, x) in iter.into_iter().enumerate() {
+            assert!(i < self.len, "`iter` is longer than the tree");
             self.data[start + i] = x;
         }
-        for i in (0..start).rev() {
-            let l = 2 * i + 1;
-            let r = l + 1;
-            self.data[i] = self.group.add(self.data[l].clone(), self.data[r].clone());
+        self.rebuild();
+    }
+
+    /// Amortized O(log n)
+    pub fn push(&mut self, x: G::Elem) {
+        self.reserve(1);
+        self.len += 1;
+        self.update(self.len - 1, x);
+    }
+
+    /// O(log n)
+    pub fn pop(&mut self) -> Option<G::Elem> {
+        if self.is_empty() {
+            return None;
+        }
+
+        let i = self.start() + self.len - 1;
+        let x = std::mem::replace(&mut self.data[i], self.group.id());
+        self.update(self.len - 1, self.group.id());
+        self.len -= 1;
+        Some(x)
+    }
+
+    /// O(n)
+    pub fn resize_with<F: FnMut() -> G::Elem>(&mut self, size: usize, mut f: F) {
+        if size < self.len {
+            let start = self.start();
+            for x in &mut self.data[start + size..start + self.len] {
+                *x = self.group.id();
+            }
+            self.len = size;
+            self.rebuild();
+        } else {
+            self.reserve(size - self.len);
+            while self.len < size {
+                self.push(f());
+            }
         }
     }
 
+    /// Sums the elements in `l..r` in order, so `G` doesn't have to be commutative.
+    ///
     /// O(log n)
     pub fn sum(&self, mut l: usize, mut r: usize) -> G::Elem {
+        assert!(l <= r, "range start {} is greater than range end {}", l, r);
+        assert!(
+            r <= self.len,
+            "range end {} is out of bounds for length {}",
+            r,
+            self.len
+        );
+
         let start = self.start();
         l += start;
         r += start;
 
-        let mut s = self.group.id();
+        // `sl` collects the segments from the left and `sr` from the right.
+        let mut sl = self.group.id();
+        let mut sr = self.group.id();
+        while l < r {
+            if l & 1 == 0 {
+                sl = self.group.add(sl, self.data[l].clone());
+            }
+            if r & 1 == 0 {
+                r -= 1;
+                sr = self.group.add(self.data[r].clone(), sr);
+            }
+            l /= 2;
+            r /= 2;
+        }
+
+        self.group.add(sl, sr)
+    }
+
+    /// Sums the elements in `range` in order, like `sum`.
+    ///
+    /// O(log n)
+    pub fn query<R: RangeBounds<usize>>(&self, range: R) -> G::Elem {
+        let (l, r) = bounds(range, self.len);
+        self.sum(l, r)
+    }
+
+    /// Like `query`, but returns `None` if `range` is out of bounds.
+    ///
+    /// O(log n)
+    pub fn try_query<R: RangeBounds<usize>>(&self, range: R) -> Option<G::Elem> {
+        let (l, r) = try_bounds(range, self.len)?;
+        (l <= r && r <= self.len).then(|| self.sum(l, r))
+    }
+
+    /// Returns the largest `r` such that `pred(&self.sum(l, r))` holds, given that `pred` is
+    /// monotone (true up to some point and false afterwards) and `pred(&id)` holds.
+    ///
+    /// O(log n)
+    pub fn max_right<F: FnMut(&G::Elem) -> bool>(&self, l: usize, mut pred: F) -> usize {
+        assert!(
+            l <= self.len(),
+            "index {} is out of bounds for length {}",
+            l,
+            self.len()
+        );
+        assert!(
+            pred(&self.group.id()),
+            "`pred` doesn't hold for the identity"
+        );
+
+        let start = self.start();
+        let mut acc = self.group.id();
+        // The nodes from the right are visited last, in the reverse order they were found.
+        let mut right_nodes = StackVec::<usize, 64>::new();
+        let (mut l, mut r) = (l + start, self.len + start);
         while l < r {
             if l & 1 == 0 {
-                s = self.group.add(s, self.data[l].clone());
+                if let Some(i) = self.descend_right(l, &mut acc, &mut pred) {
+                    return i;
+                }
             }
             if r & 1 == 0 {
                 r -= 1;
-                s = self.group.add(s, self.data[r].clone());
+                assert!(right_nodes.push(r).is_none());
             }
             l /= 2;
             r /= 2;
         }
+        while let Some(node) = right_nodes.pop() {
+            if let Some(i) = self.descend_right(node, &mut acc, &mut pred) {
+                return i;
+            }
+        }
 
-        s
+        self.len()
     }
 
+    /// Adds the segment of `node` to `acc` if `pred` still holds, otherwise returns the index of
+    /// the first element in the segment for which it fails.
+    fn descend_right<F: FnMut(&G::Elem) -> bool>(
+        &self,
+        mut node: usize,
+        acc: &mut G::Elem,
+        pred: &mut F,
+    ) -> Option<usize> {
+        let sum = self.group.add(acc.clone(), self.data[node].clone());
+        if pred(&sum) {
+            *acc = sum;
+            return None;
+        }
+
+        let start = self.start();
+        while node < start {
+            node = 2 * node + 1;
+            let sum = self.group.add(acc.clone(), self.data[node].clone());
+            if pred(&sum) {
+                *acc = sum;
+                node += 1;
+            }
+        }
+        Some(node - start)
+    }
+
+    /// Returns the smallest `l` such that `pred(&self.sum(l, r))` holds, given that `pred` is
+    /// monotone (true down to some point and false before it) and `pred(&id)` holds.
+    ///
     /// O(log n)
-    pub fn update(&mut self, mut i: usize, x: G::Elem) {
-        i += self.start();
-        self.data[i] = x;
+    pub fn min_left<F: FnMut(&G::Elem) -> bool>(&self, r: usize, mut pred: F) -> usize {
+        assert!(
+            r <= self.len(),
+            "index {} is out of bounds for length {}",
+            r,
+            self.len()
+        );
+        assert!(
+            pred(&self.group.id()),
+            "`pred` doesn't hold for the identity"
+        );
+
+        let start = self.start();
+        let mut acc = self.group.id();
+        // The nodes from the left are visited last, in the reverse order they were found.
+        let mut left_nodes = StackVec::<usize, 64>::new();
+        let (mut l, mut r) = (start, r + start);
+        while l < r {
+            if l & 1 == 0 {
+                assert!(left_nodes.push(l).is_none());
+            }
+            if r & 1 == 0 {
+                r -= 1;
+                if let Some(i) = self.descend_left(r, &mut acc, &mut pred) {
+                    return i;
+                }
+            }
+            l /= 2;
+            r /= 2;
+        }
+        while let Some(node) = left_nodes.pop() {
+            if let Some(i) = self.descend_left(node, &mut acc, &mut pred) {
+                return i;
+            }
+        }
+
+        0
+    }
+
+    /// Adds the segment of `node` to `acc` from the left if `pred` still holds, otherwise returns
+    /// the index after the last element in the segment for which it fails.
+    fn descend_left<F: FnMut(&G::Elem) -> bool>(
+        &self,
+        mut node: usize,
+        acc: &mut G::Elem,
+        pred: &mut F,
+    ) -> Option<usize> {
+        let sum = self.group.add(self.data[node].clone(), acc.clone());
+        if pred(&sum) {
+            *acc = sum;
+            return None;
+        }
+
+        let start = self.start();
+        while node < start {
+            node = 2 * node + 2;
+            let sum = self.group.add(self.data[node].clone(), acc.clone());
+            if pred(&sum) {
+                *acc = sum;
+                node -= 1;
+            }
+        }
+        Some(node - start + 1)
+    }
+
+    /// O(log n)
+    pub fn update(&mut self, i: usize, x: G::Elem) {
+        self.modify(i, |elem| *elem = x);
+    }
+
+    /// Calls `f` on the `i`th element and then recalculates its ancestors.
+    ///
+    /// O(log n)
+    pub fn modify<F: FnOnce(&mut G::Elem)>(&mut self, i: usize, f: F) {
+        assert!(
+            i < self.len,
+            "index {} is out of bounds for length {}",
+            i,
+            self.len
+        );
+        let mut i = i + self.start();
+        f(&mut self.data[i]);
 
         while i != 0 {
             i = (i - 1) / 2;
@@ -73,6 +385,31 @@ impl<G: CommutativeMonoid> SegmentTree<G> {
 
     /// O(1)
     pub fn get(&self, i: usize) -> &G::Elem {
+        assert!(
+            i < self.len,
+            "index {} is out of bounds for length {}",
+            i,
+            self.len
+        );
         &self.data[self.start() + i]
     }
+
+    /// The elements, use `as_slice().get(i)` for a non-panicking `get`.
+    ///
+    /// O(1)
+    pub fn as_slice(&self) -> &[G::Elem] {
+        let start = self.start();
+        &self.data[start..start + self.len]
+    }
+}
+
+impl<G: Monoid> Extend<G::Elem> for SegmentTree<G> {
+    fn extend<Iter: IntoIterator<Item = G::Elem>>(&mut self, iter: Iter) {
+        let iter = iter.into_iter();
+        self.reserve(iter.size_hint().0);
+
+        for x in iter {
+            self.push(x);
+        }
+    }
 }
@@ -0,0 +1,154 @@
+use crate::groups::CommutativeMonoid;
+use std::ops::Range;
+
+/// A segment tree of segment trees over a `width` by `height` grid, with the same layout as
+/// `SegmentTree` in both dimensions.
+#[derive(Clone, Debug)]
+pub struct SegmentTree2D<G: CommutativeMonoid> {
+    group: G,
+    width: usize,
+    height: usize,
+    data: Vec<G::Elem>,
+}
+
+impl<G: CommutativeMonoid> SegmentTree2D<G> {
+    #[inline]
+    pub fn new(group: G, width: usize, height: usize) -> Self {
+        let mut data = Vec::new();
+        data.resize_with(
+            (2 * width).saturating_sub(1) * (2 * height).saturating_sub(1),
+            || group.id(),
+        );
+        Self {
+            group,
+            width,
+            height,
+            data,
+        }
+    }
+
+    #[inline]
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    #[inline]
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// The index of the `j`th node of the `i`th row.
+    #[inline]
+    fn index(&self, i: usize, j: usize) -> usize {
+        i * (2 * self.height - 1) + j
+    }
+
+    /// Sums the elements of the `i`th row in `l..r`.
+    fn row_sum(&self, i: usize, mut l: usize, mut r: usize) -> G::Elem {
+        l += self.height - 1;
+        r += self.height - 1;
+
+        let mut s = self.group.id();
+        while l < r {
+            if l & 1 == 0 {
+                s = self.group.add(s, self.data[self.index(i, l)].clone());
+            }
+            if r & 1 == 0 {
+                r -= 1;
+                s = self.group.add(self.data[self.index(i, r)].clone(), s);
+            }
+            l /= 2;
+            r /= 2;
+        }
+        s
+    }
+
+    /// Sums the elements in the rectangle `xs` by `ys`.
+    ///
+    /// O(log w * log h)
+    pub fn sum(&self, xs: Range<usize>, ys: Range<usize>) -> G::Elem {
+        assert!(
+            xs.start <= xs.end && xs.end <= self.width,
+            "range {:?} is out of bounds for width {}",
+            xs,
+            self.width
+        );
+        assert!(
+            ys.start <= ys.end && ys.end <= self.height,
+            "range {:?} is out of bounds for height {}",
+            ys,
+            self.height
+        );
+        if xs.is_empty() || ys.is_empty() {
+            return self.group.id();
+        }
+
+        let mut l = xs.start + self.width - 1;
+        let mut r = xs.end + self.width - 1;
+        let mut s = self.group.id();
+        while l < r {
+            if l & 1 == 0 {
+                s = self.group.add(s, self.row_sum(l, ys.start, ys.end));
+            }
+            if r & 1 == 0 {
+                r -= 1;
+                s = self.group.add(self.row_sum(r, ys.start, ys.end), s);
+            }
+            l /= 2;
+            r /= 2;
+        }
+        s
+    }
+
+    /// O(log w * log h)
+    pub fn update(&mut self, x: usize, y: usize, v: G::Elem) {
+        assert!(
+            x < self.width && y < self.height,
+            "index ({}, {}) is out of bounds for size ({}, {})",
+            x,
+            y,
+            self.width,
+            self.height
+        );
+
+        let mut i = x + self.width - 1;
+        let mut j = y + self.height - 1;
+        let leaf = self.index(i, j);
+        self.data[leaf] = v;
+        while j != 0 {
+            j = (j - 1) / 2;
+            let (l, r) = (self.index(i, 2 * j + 1), self.index(i, 2 * j + 2));
+            let node = self.index(i, j);
+            self.data[node] = self.group.add(self.data[l].clone(), self.data[r].clone());
+        }
+
+        while i != 0 {
+            i = (i - 1) / 2;
+
+            let mut j = y + self.height - 1;
+            loop {
+                let l = self.index(2 * i + 1, j);
+                let r = self.index(2 * i + 2, j);
+                let node = self.index(i, j);
+                self.data[node] = self.group.add(self.data[l].clone(), self.data[r].clone());
+                if j == 0 {
+                    break;
+                }
+                j = (j - 1) / 2;
+            }
+        }
+    }
+
+    /// O(1)
+    pub fn get(&self, x: usize, y: usize) -> &G::Elem {
+        assert!(
+            x < self.width && y < self.height,
+            "index ({}, {}) is out of bounds for size ({}, {})",
+            x,
+            y,
+            self.width,
+            self.height
+        );
+        &self.data[self.index(x + self.width - 1, y + self.height - 1)]
+    }
+}
@@ -0,0 +1,179 @@
+use crate::groups::Monoid;
+use num::PrimInt;
+
+/// The index of a missing child, the root is never a child so it can't be confused with it.
+const NONE: usize = 0;
+
+#[derive(Clone, Debug)]
+struct Node<T> {
+    sum: T,
+    children: [usize; 2],
+}
+
+/// A segment tree over the indices `lo..hi`, which only creates the nodes that were updated.
+#[derive(Clone, Debug)]
+pub struct SparseSegmentTree<G: Monoid, I: PrimInt = i64> {
+    group: G,
+    id: G::Elem,
+    lo: I,
+    hi: I,
+    nodes: Vec<Node<G::Elem>>,
+}
+
+/// Rounds `(lo + hi) / 2` down without overflowing.
+#[inline]
+fn mid<I: PrimInt>(lo: I, hi: I) -> I {
+    (lo & hi) + ((lo ^ hi) >> 1)
+}
+
+impl<G: Monoid, I: PrimInt> SparseSegmentTree<G, I> {
+    #[inline]
+    pub fn new(group: G, lo: I, hi: I) -> Self {
+        assert!(lo < hi, "the index range must not be empty");
+        let id = group.id();
+        let root = Node {
+            sum: group.id(),
+            children: [NONE; 2],
+        };
+
+        Self {
+            group,
+            id,
+            lo,
+            hi,
+            nodes: vec![root],
+        }
+    }
+
+    /// The range of the indices, `lo..hi`.
+    #[inline]
+    pub fn bounds(&self) -> (I, I) {
+        (self.lo, self.hi)
+    }
+
+    /// The number of nodes that were created.
+    #[inline]
+    pub fn num_nodes(&self) -> usize {
+        self.nodes.len()
+    }
+
+    fn node_sum(&self, node: usize) -> G::Elem {
+        match node {
+            NONE => self.group.id(),
+            _ => self.nodes[node].sum.clone(),
+        }
+    }
+
+    /// Sums the elements in `l..r` in order.
+    ///
+    /// O(log (hi - lo))
+    pub fn sum(&self, l: I, r: I) -> G::Elem {
+        fn sum_rec<G: Monoid, I: PrimInt>(
+            slf: &SparseSegmentTree<G, I>,
+            node: usize,
+            lo: I,
+            hi: I,
+            l: I,
+            r: I,
+        ) -> G::Elem {
+            if r <= lo || hi <= l {
+                return slf.group.id();
+            }
+            if l <= lo && hi <= r {
+                return slf.nodes[node].sum.clone();
+            }
+
+            let mid = mid(lo, hi);
+            let [left, right] = slf.nodes[node].children;
+            let sl = match left {
+                NONE => slf.group.id(),
+                _ => sum_rec(slf, left, lo, mid, l, r),
+            };
+            let sr = match right {
+                NONE => slf.group.id(),
+                _ => sum_rec(slf, right, mid, hi, l, r),
+            };
+            slf.group.add(sl, sr)
+        }
+
+        assert!(l <= r, "range start is greater than range end");
+        assert!(
+            self.lo <= l && r <= self.hi,
+            "range is out of the bounds of the tree"
+        );
+        if l < r {
+            sum_rec(self, 0, self.lo, self.hi, l, r)
+        } else {
+            self.group.id()
+        }
+    }
+
+    /// O(log (hi - lo))
+    pub fn update(&mut self, i: I, x: G::Elem) {
+        assert!(
+            self.lo <= i && i < self.hi,
+            "index is out of the bounds of the tree"
+        );
+
+        let (mut lo, mut hi) = (self.lo, self.hi);
+        let mut node = 0;
+        let mut path = Vec::new();
+        while lo + I::one() != hi {
+            let mid = mid(lo, hi);
+            let side = if i < mid {
+                hi = mid;
+                0
+            } else {
+                lo = mid;
+                1
+            };
+
+            path.push(node);
+            node = match self.nodes[node].children[side] {
+                NONE => {
+                    let child = self.nodes.len();
+                    self.nodes.push(Node {
+                        sum: self.group.id(),
+                        children: [NONE; 2],
+                    });
+                    self.nodes[node].children[side] = child;
+                    child
+                }
+                child => child,
+            };
+        }
+        self.nodes[node].sum = x;
+
+        for node in path.into_iter().rev() {
+            let [left, right] = self.nodes[node].children;
+            self.nodes[node].sum = self.group.add(self.node_sum(left), self.node_sum(right));
+        }
+    }
+
+    /// O(log (hi - lo))
+    pub fn get(&self, i: I) -> &G::Elem {
+        assert!(
+            self.lo <= i && i < self.hi,
+            "index is out of the bounds of the tree"
+        );
+
+        let (mut lo, mut hi) = (self.lo, self.hi);
+        let mut node = 0;
+        while lo + I::one() != hi {
+            let mid = mid(lo, hi);
+            let side = if i < mid {
+                hi = mid;
+                0
+            } else {
+                lo = mid;
+                1
+            };
+
+            node = match self.nodes[node].children[side] {
+                NONE => return &self.id,
+                child => child,
+            };
+        }
+        &self.nodes[node].sum
+    }
+}
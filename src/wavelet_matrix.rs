@@ -0,0 +1,296 @@
+use crate::segment_tree::bounds;
+use std::iter::FromIterator;
+use std::ops::{Bound, RangeBounds};
+
+/// A bit vector with O(1) `rank`.
+#[derive(Clone, Debug, Default)]
+struct BitVector {
+    len: usize,
+    blocks: Vec<u64>,
+    /// The number of ones before each block.
+    ranks: Vec<usize>,
+}
+
+impl BitVector {
+    fn new<Iter: IntoIterator<Item = bool>>(iter: Iter) -> Self {
+        let mut slf = Self::default();
+        for b in iter {
+            if slf.len % 64 == 0 {
+                slf.blocks.push(0);
+            }
+            slf.blocks[slf.len / 64] |= (b as u64) << (slf.len % 64);
+            slf.len += 1;
+        }
+
+        let mut rank = 0;
+        for block in &slf.blocks {
+            slf.ranks.push(rank);
+            rank += block.count_ones() as usize;
+        }
+        slf.ranks.push(rank);
+        slf
+    }
+
+    #[inline]
+    fn get(&self, i: usize) -> bool {
+        self.blocks[i / 64] >> (i % 64) & 1 != 0
+    }
+
+    /// The number of ones in `0..i`.
+    ///
+    /// O(1)
+    #[inline]
+    fn rank1(&self, i: usize) -> usize {
+        match i % 64 {
+            0 => self.ranks[i / 64],
+            r => self.ranks[i / 64] + (self.blocks[i / 64] << (64 - r)).count_ones() as usize,
+        }
+    }
+
+    /// The number of zeros in `0..i`.
+    ///
+    /// O(1)
+    #[inline]
+    fn rank0(&self, i: usize) -> usize {
+        i - self.rank1(i)
+    }
+
+    /// The position of the `k`th (from 0) `b`, which has to exist.
+    ///
+    /// O(log n)
+    fn select(&self, b: bool, k: usize) -> usize {
+        let rank = |i| match b {
+            true => self.rank1(i),
+            false => self.rank0(i),
+        };
+
+        // The smallest `i` such that `k < rank(i + 1)`.
+        let (mut lo, mut hi) = (0, self.len);
+        while lo < hi {
+            let mid = (lo + hi) / 2;
+            if k < rank(mid + 1) {
+                hi = mid;
+            } else {
+                lo = mid + 1;
+            }
+        }
+        lo
+    }
+}
+
+/// A static sequence which answers rank, select and order statistics queries over ranges in
+/// O(log σ), where σ is the number of distinct values.
+#[derive(Clone, Debug)]
+pub struct WaveletMatrix<T: Ord> {
+    len: usize,
+    /// The sorted distinct values, the matrix holds the indices into it.
+    values: Vec<T>,
+    /// The levels from the most significant bit to the least.
+    levels: Vec<BitVector>,
+    /// The number of zeros in each level.
+    zeros: Vec<usize>,
+}
+
+impl<T: Ord + Clone> WaveletMatrix<T> {
+    /// O(n log σ)
+    pub fn new<Iter: IntoIterator<Item = T>>(iter: Iter) -> Self {
+        let elements: Vec<T> = iter.into_iter().collect();
+        let mut values = elements.clone();
+        values.sort_unstable();
+        values.dedup();
+
+        let mut codes: Vec<usize> = elements
+            .iter()
+            .map(|x| values.binary_search(x).unwrap())
+            .collect();
+
+        let num_bits = (usize::BITS - values.len().saturating_sub(1).leading_zeros()) as usize;
+        let mut levels = Vec::with_capacity(num_bits);
+        let mut zeros = Vec::with_capacity(num_bits);
+        for bit in (0..num_bits).rev() {
+            levels.push(BitVector::new(codes.iter().map(|&c| c >> bit & 1 != 0)));
+
+            let (mut lower, upper): (Vec<_>, Vec<_>) =
+                codes.iter().partition(|&&c| c >> bit & 1 == 0);
+            zeros.push(lower.len());
+            lower.extend(upper);
+            codes = lower;
+        }
+
+        Self {
+            len: elements.len(),
+            values,
+            levels,
+            zeros,
+        }
+    }
+}
+
+impl<T: Ord> WaveletMatrix<T> {
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The number of levels, which is the number of bits of the indices into `values`.
+    #[inline]
+    fn num_bits(&self) -> usize {
+        self.levels.len()
+    }
+
+    /// Moves `l..r` of a level to the part of the next level with the bit `b`.
+    #[inline]
+    fn descend(&self, level: usize, b: bool, l: usize, r: usize) -> (usize, usize) {
+        let bv = &self.levels[level];
+        match b {
+            false => (bv.rank0(l), bv.rank0(r)),
+            true => (
+                self.zeros[level] + bv.rank1(l),
+                self.zeros[level] + bv.rank1(r),
+            ),
+        }
+    }
+
+    /// O(log σ)
+    pub fn get(&self, mut i: usize) -> &T {
+        assert!(
+            i < self.len(),
+            "index {} is out of bounds for length {}",
+            i,
+            self.len()
+        );
+
+        let mut code = 0;
+        for level in 0..self.num_bits() {
+            let b = self.levels[level].get(i);
+            code = code << 1 | b as usize;
+            i = self.descend(level, b, i, i).0;
+        }
+        &self.values[code]
+    }
+
+    /// The number of occurrences of `x` in `0..i`.
+    ///
+    /// O(log σ)
+    pub fn rank(&self, x: &T, i: usize) -> usize {
+        let (_, i) = bounds(..i, self.len());
+        let code = match self.values.binary_search(x) {
+            Ok(code) => code,
+            Err(_) => return 0,
+        };
+
+        let (mut l, mut r) = (0, i);
+        for level in 0..self.num_bits() {
+            let b = code >> (self.num_bits() - 1 - level) & 1 != 0;
+            (l, r) = self.descend(level, b, l, r);
+        }
+        r - l
+    }
+
+    /// The position of the `k`th (from 0) occurrence of `x`, or `None` if there're at most `k`
+    /// occurrences.
+    ///
+    /// O(log σ log n)
+    pub fn select(&self, x: &T, k: usize) -> Option<usize> {
+        let code = self.values.binary_search(x).ok()?;
+
+        let (mut l, mut r) = (0, self.len());
+        for level in 0..self.num_bits() {
+            let b = code >> (self.num_bits() - 1 - level) & 1 != 0;
+            (l, r) = self.descend(level, b, l, r);
+        }
+        if r - l <= k {
+            return None;
+        }
+
+        let mut i = l + k;
+        for level in (0..self.num_bits()).rev() {
+            let b = code >> (self.num_bits() - 1 - level) & 1 != 0;
+            i = match b {
+                false => self.levels[level].select(false, i),
+                true => self.levels[level].select(true, i - self.zeros[level]),
+            };
+        }
+        Some(i)
+    }
+
+    /// The `k`th (from 0) smallest element in `range`, or `None` if `range` has at most `k`
+    /// elements.
+    ///
+    /// O(log σ)
+    pub fn quantile<R: RangeBounds<usize>>(&self, range: R, mut k: usize) -> Option<&T> {
+        let (mut l, mut r) = bounds(range, self.len());
+        if r - l <= k {
+            return None;
+        }
+
+        let mut code = 0;
+        for level in 0..self.num_bits() {
+            let bv = &self.levels[level];
+            let zeros = bv.rank0(r) - bv.rank0(l);
+            let b = zeros <= k;
+            if b {
+                k -= zeros;
+            }
+            code = code << 1 | b as usize;
+            (l, r) = self.descend(level, b, l, r);
+        }
+        Some(&self.values[code])
+    }
+
+    /// The number of elements in `l..r` whose index into `values` is smaller than `code`.
+    fn count_less(&self, mut l: usize, mut r: usize, code: usize) -> usize {
+        if code >> self.num_bits() != 0 {
+            return r - l;
+        }
+
+        let mut count = 0;
+        for level in 0..self.num_bits() {
+            let b = code >> (self.num_bits() - 1 - level) & 1 != 0;
+            if b {
+                let bv = &self.levels[level];
+                count += bv.rank0(r) - bv.rank0(l);
+            }
+            (l, r) = self.descend(level, b, l, r);
+        }
+        count
+    }
+
+    /// The number of elements in `range` which are in `values`.
+    ///
+    /// O(log σ)
+    pub fn range_freq<R: RangeBounds<usize>, V: RangeBounds<T>>(
+        &self,
+        range: R,
+        values: V,
+    ) -> usize {
+        let (l, r) = bounds(range, self.len());
+        let lower = match values.start_bound() {
+            Bound::Included(x) => self.values.partition_point(|v| v < x),
+            Bound::Excluded(x) => self.values.partition_point(|v| v <= x),
+            Bound::Unbounded => 0,
+        };
+        let upper = match values.end_bound() {
+            Bound::Included(x) => self.values.partition_point(|v| v <= x),
+            Bound::Excluded(x) => self.values.partition_point(|v| v < x),
+            Bound::Unbounded => self.values.len(),
+        };
+
+        if lower < upper {
+            self.count_less(l, r, upper) - self.count_less(l, r, lower)
+        } else {
+            0
+        }
+    }
+}
+
+impl<T: Ord + Clone> FromIterator<T> for WaveletMatrix<T> {
+    fn from_iter<Iter: IntoIterator<Item = T>>(iter: Iter) -> Self {
+        Self::new(iter)
+    }
+}